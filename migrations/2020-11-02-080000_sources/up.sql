@@ -0,0 +1,57 @@
+-- Your SQL goes here
+
+-- spider sources: start url and the rules to pick links,
+-- all rules empty: the per-domain rules in bot/cfg.rs, or a RSS/Atom feed
+CREATE TABLE sources (
+  id INTEGER PRIMARY KEY DEFAULT nextval('serial_seq'),
+  url VARCHAR UNIQUE NOT NULL,
+  author VARCHAR NOT NULL DEFAULT '',
+  topic VARCHAR NOT NULL DEFAULT '',
+  link_prefix VARCHAR NOT NULL DEFAULT '',   -- keep link starts with
+  link_regex VARCHAR NOT NULL DEFAULT '',    -- keep link matches
+  link_selector VARCHAR NOT NULL DEFAULT '', -- css selector to link, default: a
+  is_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  add_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- the sources was compiled in bot/cfg.rs
+INSERT INTO sources (url, author, topic) VALUES
+  ('https://blog.rust-lang.org/feed.xml', 'Rust Team', 'Rust'),
+  ('https://users.rust-lang.org/top', 'Rust Forum', 'Rust'),
+  ('https://internals.rust-lang.org/top', 'Rust Forum', 'Rust'),
+  ('http://smallcultfollowing.com/babysteps/atom.xml', 'Nicholas Matsakis', 'Rust'),
+  ('https://tokio.rs/blog/2019-11-tokio-0-2/', 'Tokio Team', 'Rust'),
+  ('https://async.rs/blog/', 'async-std', 'Rust'),
+  ('https://blog.guillaume-gomez.fr/', 'Guillaume Gomez', 'Rust'),
+  ('https://www.ralfj.de/blog/', 'Ralf Jung', 'Rust'),
+  ('https://fitzgeraldnick.com/', 'Nick Fitzgerald', 'Rust'),
+  ('https://deterministic.space/', 'Pascal Hertleif', 'Rust'),
+  ('https://without.boats/blog/', 'Without Boats', 'Rust'),
+  ('https://www.pietroalbini.org/', 'Pietro Albini', 'Rust'),
+  ('https://fnordig.de/posts/', 'Jan-Erik Rediger', 'Rust'),
+  ('https://kazlauskas.me/', 'Simonas Kazlauskas', 'Rust'),
+  ('https://manishearth.github.io/', 'Manish Goregaokar', 'Rust'),
+  ('https://www.ncameron.org/blog/', 'Nick Cameron', 'Rust'),
+  ('https://blog.japaric.io/', 'Jorge Aparicio', 'Rust'),
+  ('https://words.steveklabnik.com/', 'Steve Klabnik', 'Rust'),
+  ('https://blog.digital-scurf.org/', 'Daniel Silverstone', 'Rust'),
+  ('https://blog.x5ff.xyz/blog/', 'Claus Matzinger', 'Rust'),
+  ('https://llogiq.github.io/', 'Andre Bogus', 'Rust'),
+  ('https://tonyarcieri.com/', 'Tony Arcieri', 'Rust'),
+  ('https://blog.yoshuawuyts.com/', 'Yoshua Wuyts', 'Rust'),
+  ('https://seanmonstar.com/', 'Sean McArthur', 'Rust'),
+  ('https://blog.ryanlevick.com/', 'Ryan Levick', 'Rust'),
+  ('https://matklad.github.io/feed.xml', 'Aleksey Kladov', 'Rust'),
+  ('https://blog.troutwine.us/', 'Troutwine', 'Rust'),
+  ('https://rust-embedded.github.io/blog/', 'Embedded Rust', 'Rust'),
+  ('https://rustacean-station.org/', 'rustacean-station', 'Rust'),
+  ('https://raphlinus.github.io/', 'Raph Levien', 'Rust'),
+  ('https://lucumr.pocoo.org/', 'Armin Ronacher', 'Rust'),
+  ('https://stjepang.github.io/', 'Stjepan Glavina', 'Rust'),
+  ('https://blog.bastion.rs/', 'bastion.rs', 'Rust'),
+  ('https://yaah.dev/', 'Jane Lusby', 'Rust'),
+  ('https://levpaul.com/', 'Levi Lovelock', 'Rust'),
+  ('https://www.fluentcpp.com/', 'Jonathan Boccara', 'CPP'),
+  ('https://blog.golang.org/feed.atom', 'Go Team', 'Go'),
+  ('https://research.swtch.com/', 'Russ Cox', 'Go'),
+  ('https://hacks.mozilla.org/feed/', 'Mozilla', 'Web');
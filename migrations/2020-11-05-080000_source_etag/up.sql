@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- the validators of the last fetch, for conditional GET
+ALTER TABLE sources
+  ADD COLUMN etag VARCHAR NOT NULL DEFAULT '',
+  ADD COLUMN last_modified VARCHAR NOT NULL DEFAULT '';
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX items_dup_of_idx;
+
+ALTER TABLE items
+  DROP COLUMN simhash,
+  DROP COLUMN dup_of;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- simhash of title and content, 0: not computed or too short;
+-- dup_of: the earlier item this one likely duplicates, hidden in listings
+ALTER TABLE items
+  ADD COLUMN simhash BIGINT NOT NULL DEFAULT 0,
+  ADD COLUMN dup_of INTEGER REFERENCES items (id) ON DELETE SET NULL;
+
+CREATE INDEX items_dup_of_idx ON items (dup_of);
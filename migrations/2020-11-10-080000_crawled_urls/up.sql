@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- the urls seen by bot, to diff the new links
+CREATE TABLE crawled_urls (
+  url VARCHAR PRIMARY KEY,
+  first_seen TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_checked TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- seed from items, both the link and a rough canonical form of it
+INSERT INTO crawled_urls (url, first_seen, last_checked)
+SELECT link, post_at, post_at FROM items WHERE link <> ''
+ON CONFLICT DO NOTHING;
+
+INSERT INTO crawled_urls (url, first_seen, last_checked)
+SELECT 
+  regexp_replace(
+    regexp_replace(
+      regexp_replace(link, '#.*$', ''),
+      '^https?://(www\.)?', 'https://'
+    ),
+    '/+$', ''
+  ),
+  post_at, 
+  post_at 
+FROM items WHERE link <> ''
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- a record per fetch of bot
+CREATE TABLE crawl_logs (
+  id INTEGER PRIMARY KEY DEFAULT nextval('serial_seq'),
+  run_at TIMESTAMP NOT NULL,  -- the same for all fetches in a run
+  kind VARCHAR NOT NULL DEFAULT 'source', -- source|page
+  url VARCHAR NOT NULL,
+  source_id INTEGER REFERENCES sources (id) ON DELETE CASCADE,
+  status INTEGER NOT NULL DEFAULT 0, -- 0: failed w/o response
+  duration_ms INTEGER NOT NULL DEFAULT 0,
+  error VARCHAR NOT NULL DEFAULT '',
+  links_found INTEGER NOT NULL DEFAULT 0,
+  crawl_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX crawl_logs_run_at_idx ON crawl_logs (run_at);
+CREATE INDEX crawl_logs_source_id_idx ON crawl_logs (source_id);
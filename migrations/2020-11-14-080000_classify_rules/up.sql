@@ -0,0 +1,79 @@
+-- Your SQL goes here
+-- keyword weights to classify the topic and type of bot items
+CREATE TABLE classify_rules (
+  id INTEGER PRIMARY KEY DEFAULT nextval('serial_seq'),
+  kw VARCHAR NOT NULL,      -- a word or phrase, case-insensitive
+  kind VARCHAR NOT NULL,    -- topic|ty
+  target VARCHAR NOT NULL,  -- a topic in TOPIC_VEC, or a type in TY_VEC
+  weight INTEGER NOT NULL DEFAULT 1,
+  UNIQUE (kw, kind, target)
+);
+
+INSERT INTO classify_rules (kw, kind, target, weight) VALUES
+('rust', 'topic', 'Rust', 3),
+('rustc', 'topic', 'Rust', 3),
+('cargo', 'topic', 'Rust', 2),
+('crates.io', 'topic', 'Rust', 2),
+('tokio', 'topic', 'Rust', 2),
+('actix', 'topic', 'Rust', 2),
+('golang', 'topic', 'Go', 3),
+('goroutine', 'topic', 'Go', 3),
+('goroutines', 'topic', 'Go', 3),
+('swift', 'topic', 'Swift', 3),
+('swiftui', 'topic', 'Swift', 3),
+('xcode', 'topic', 'Swift', 2),
+('typescript', 'topic', 'TypeScript', 3),
+('deno', 'topic', 'TypeScript', 2),
+('dart', 'topic', 'Dart', 3),
+('flutter', 'topic', 'Dart', 3),
+('python', 'topic', 'Python', 3),
+('django', 'topic', 'Python', 2),
+('flask', 'topic', 'Python', 2),
+('pandas', 'topic', 'Python', 2),
+('c#', 'topic', 'C-sharp', 3),
+('.net', 'topic', 'C-sharp', 2),
+('dotnet', 'topic', 'C-sharp', 2),
+('c++', 'topic', 'CPP', 3),
+('cpp', 'topic', 'CPP', 3),
+('c language', 'topic', 'C', 3),
+('gcc', 'topic', 'C', 1),
+('javascript', 'topic', 'JavaScript', 3),
+('node.js', 'topic', 'JavaScript', 2),
+('nodejs', 'topic', 'JavaScript', 2),
+('react', 'topic', 'JavaScript', 2),
+('vue', 'topic', 'JavaScript', 2),
+('npm', 'topic', 'JavaScript', 1),
+('java', 'topic', 'Java', 3),
+('jvm', 'topic', 'Java', 2),
+('spring boot', 'topic', 'Java', 2),
+('php', 'topic', 'PHP', 3),
+('laravel', 'topic', 'PHP', 2),
+('symfony', 'topic', 'PHP', 2),
+('kotlin', 'topic', 'Kotlin', 3),
+('database', 'topic', 'DataBase', 2),
+('postgres', 'topic', 'DataBase', 3),
+('postgresql', 'topic', 'DataBase', 3),
+('mysql', 'topic', 'DataBase', 3),
+('sqlite', 'topic', 'DataBase', 3),
+('redis', 'topic', 'DataBase', 2),
+('mongodb', 'topic', 'DataBase', 3),
+('podcast', 'ty', 'Media', 3),
+('episode', 'ty', 'Media', 2),
+('video', 'ty', 'Media', 1),
+('screencast', 'ty', 'Media', 2),
+('youtube', 'ty', 'Media', 1),
+('we''re hiring', 'ty', 'Job', 3),
+('is hiring', 'ty', 'Job', 3),
+('job opening', 'ty', 'Job', 3),
+('remote job', 'ty', 'Job', 2),
+('release', 'ty', 'Project', 1),
+('released', 'ty', 'Project', 1),
+('announcing', 'ty', 'Project', 1),
+('conference', 'ty', 'Event', 2),
+('meetup', 'ty', 'Event', 2),
+('workshop', 'ty', 'Event', 1),
+('call for proposals', 'ty', 'Event', 2),
+('ebook', 'ty', 'Book', 2),
+('book', 'ty', 'Book', 1),
+('translation', 'ty', 'Translate', 1)
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE link_checks;
+
+ALTER TABLE items
+  DROP COLUMN is_dead;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- the link of item is dead in a few checks in a row
+ALTER TABLE items
+  ADD COLUMN is_dead BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- the last check of the link of item
+CREATE TABLE link_checks (
+  item_id INTEGER PRIMARY KEY REFERENCES items (id) ON DELETE CASCADE,
+  url VARCHAR NOT NULL,
+  status INTEGER NOT NULL DEFAULT 0,         -- final response, 0: no response
+  final_url VARCHAR NOT NULL DEFAULT '',     -- redirect target, empty if not
+  permanent BOOLEAN NOT NULL DEFAULT FALSE,  -- all redirects 301 or 308
+  error VARCHAR NOT NULL DEFAULT '',
+  fails INTEGER NOT NULL DEFAULT 0,          -- dead in checks in a row
+  check_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX link_checks_check_at_idx ON link_checks (check_at);
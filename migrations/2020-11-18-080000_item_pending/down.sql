@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE crawled_urls
+  DROP COLUMN is_rejected;
+
+DROP INDEX items_is_pending_idx;
+
+ALTER TABLE items
+  DROP COLUMN is_pending;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- the items of bot wait for review by admin, hidden in listings
+ALTER TABLE items
+  ADD COLUMN is_pending BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX items_is_pending_idx ON items (post_at) WHERE is_pending;
+
+-- rejected by admin, never crawled again
+ALTER TABLE crawled_urls
+  ADD COLUMN is_rejected BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- every edit of item: who, when, and the fields changed
+CREATE TABLE item_revisions (
+  id SERIAL PRIMARY KEY,
+  item_id INTEGER NOT NULL REFERENCES items (id) ON DELETE CASCADE,
+  edit_by VARCHAR NOT NULL,
+  edit_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  diff JSONB NOT NULL DEFAULT '[]'  -- [{field, old, new}]
+);
+
+CREATE INDEX item_revisions_item_id_idx ON item_revisions (item_id, id);
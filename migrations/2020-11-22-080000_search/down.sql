@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX items_search_idx;
+DROP INDEX blogs_search_idx;
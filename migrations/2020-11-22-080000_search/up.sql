@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- full-text search, the expressions must be the same as ITEM_TSV and BLOG_TSV in api/search.rs
+CREATE INDEX items_search_idx ON items USING GIN ((
+  setweight(to_tsvector('english', title), 'A') ||
+  setweight(to_tsvector('english', author || ' ' || link_host), 'B') ||
+  setweight(to_tsvector('english', content), 'C')
+));
+
+CREATE INDEX blogs_search_idx ON blogs USING GIN ((
+  setweight(to_tsvector('english', aname), 'A') ||
+  setweight(to_tsvector('english', intro), 'B')
+));
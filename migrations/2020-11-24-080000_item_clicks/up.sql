@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- times the link of item opened, for hot ranking
+ALTER TABLE items
+  ADD COLUMN clicks INTEGER NOT NULL DEFAULT 0;
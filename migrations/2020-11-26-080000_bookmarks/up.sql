@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- read later, private to the user, apart from votes
+CREATE TABLE bookmarks (
+  uname VARCHAR NOT NULL REFERENCES users (uname) ON UPDATE CASCADE ON DELETE CASCADE,
+  item_id INTEGER NOT NULL REFERENCES items (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  note VARCHAR NOT NULL DEFAULT '',
+  is_read BOOLEAN NOT NULL DEFAULT FALSE,
+  save_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (uname, item_id)
+);
+
+CREATE INDEX bookmarks_uname_save_at_idx ON bookmarks (uname, save_at);
@@ -0,0 +1,52 @@
+-- Your SQL goes here
+-- the canonical form of link, the key to dedup; the link kept as is
+ALTER TABLE items
+  ADD COLUMN canonical_link VARCHAR NOT NULL DEFAULT '';
+
+-- the same rules as util::helper::canonical_url, for the existing only
+CREATE FUNCTION pg_temp.canonical_url(url VARCHAR) RETURNS VARCHAR AS $$
+DECLARE
+  u VARCHAR := regexp_replace(url, '^\s+|\s+$', '', 'g');
+  m TEXT[];
+  host TEXT;
+  path TEXT;
+  params TEXT;
+BEGIN
+  m := regexp_match(u, '^https?://([^/?#]+)([^?#]*)(\?[^#]*)?', 'i');
+  IF m IS NULL THEN
+    RETURN u;
+  END IF;
+  host := regexp_replace(regexp_replace(lower(m[1]), '(:443)+$', ''), '(:80)+$', '');
+  host := regexp_replace(host, '^(www\.)+', '');
+  path := regexp_replace(regexp_replace(coalesce(m[2], ''), '/ref=.*', ''), '/+$', '');
+  SELECT string_agg(p, '&' ORDER BY n) INTO params
+  FROM unnest(string_to_array(ltrim(coalesce(m[3], ''), '?'), '&')) WITH ORDINALITY AS t(p, n)
+  WHERE p <> ''
+    AND lower(split_part(p, '=', 1)) NOT LIKE 'utm\_%'
+    AND lower(split_part(p, '=', 1)) NOT IN ('fbclid', 'gclid', 'mc_cid', 'mc_eid', 'ref_src');
+  RETURN 'https://' || host || path || coalesce('?' || params, '');
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+UPDATE items SET canonical_link = pg_temp.canonical_url(link)
+WHERE link <> '';
+
+-- the later ones of the same canonical link are duplicates of the earliest
+UPDATE items i SET dup_of = o.first_id
+FROM (
+  SELECT canonical_link, min(id) AS first_id FROM items
+  WHERE canonical_link <> '' AND dup_of IS NULL
+  GROUP BY canonical_link
+) o
+WHERE i.canonical_link = o.canonical_link
+  AND i.id > o.first_id
+  AND i.dup_of IS NULL;
+
+-- one listed item per canonical link, the duplicates kept for merge
+CREATE UNIQUE INDEX items_canonical_link_key ON items (canonical_link)
+  WHERE canonical_link <> '' AND dup_of IS NULL;
+
+-- seen by bot, in the same form as the links diffed
+INSERT INTO crawled_urls (url, first_seen, last_checked)
+SELECT canonical_link, post_at, post_at FROM items WHERE canonical_link <> ''
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- who opened the link of item, count once per user
+CREATE TABLE clickitems (
+  uname VARCHAR NOT NULL REFERENCES users (uname) ON UPDATE CASCADE ON DELETE CASCADE,
+  item_id INTEGER NOT NULL REFERENCES items (id) ON UPDATE CASCADE ON DELETE CASCADE,
+  click_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (uname, item_id)
+);
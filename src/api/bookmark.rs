@@ -0,0 +1,401 @@
+// api.bookmark: read later, private to the user, apart from votes
+
+use actix::{Handler, Message};
+use actix_web::{
+    web::{Data, Json, Path, Query},
+    Error, HttpResponse, ResponseError,
+    Result,
+};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+use log::error;
+
+use crate::errors::{ServiceError, ServiceResult};
+use crate::api::{PageQuery, auth::{CheckUser, CheckCsrf}};
+use crate::api::item::Item;
+use crate::{Dba, DbAddr, PooledConn};
+use crate::schema::{bookmarks};
+
+// max chars of a note
+pub const MAX_NOTE_LEN: usize = 512;
+
+// POST: /api/bookmarks
+//
+// save an item, or update the note if saved
+pub async fn new(
+    nb: Json<SaveItem>,
+    auth: CheckUser,
+    _csrf: CheckCsrf,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let b = NewBookmark {
+        uname: auth.uname,
+        item_id: nb.item_id,
+        note: nb.note.clone(),
+    };
+
+    if let Err(e) = b.validate() {
+        error!("{}", e);
+        return Ok(e.error_response());
+    }
+
+    let res = db.send(b).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<NewBookmark> for Dba {
+    type Result = ServiceResult<Bookmark>;
+
+    fn handle(&mut self, nb: NewBookmark, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        nb.save(conn)
+    }
+}
+
+// GET: /api/bookmarks?read=true|false&perpage=42&page=1
+//
+// of the caller only, the latest saved first
+pub async fn get_list(
+    rq: Query<ReadQuery>,
+    pq: Query<PageQuery>,
+    auth: CheckUser,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let q = QueryBookmarks {
+        uname: auth.uname,
+        read: rq.read,
+        perpage: pq.perpage.unwrap_or(42),
+        page: pq.page.unwrap_or(1),
+    };
+    let res = db.send(q).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<QueryBookmarks> for Dba {
+    type Result = ServiceResult<SavedMsg>;
+
+    fn handle(&mut self, q: QueryBookmarks, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        q.get(conn)
+    }
+}
+
+// PUT: /api/bookmarks/{item_id}
+//
+// {note?, is_read?}
+pub async fn update(
+    p: Path<i32>,
+    ch: Json<BookmarkChange>,
+    auth: CheckUser,
+    _csrf: CheckCsrf,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let eb = EditBookmark {
+        uname: auth.uname,
+        item_id: p.into_inner(),
+        change: ch.into_inner(),
+    };
+
+    if let Err(e) = eb.validate() {
+        error!("{}", e);
+        return Ok(e.error_response());
+    }
+
+    let res = db.send(eb).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<EditBookmark> for Dba {
+    type Result = ServiceResult<Bookmark>;
+
+    fn handle(&mut self, eb: EditBookmark, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        eb.update(conn)
+    }
+}
+
+// DELETE: /api/bookmarks/{item_id}
+//
+pub async fn del(
+    p: Path<i32>,
+    auth: CheckUser,
+    _csrf: CheckCsrf,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let db_msg = DelBookmark {
+        uname: auth.uname,
+        item_id: p.into_inner(),
+    };
+    let res = db.send(db_msg).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<DelBookmark> for Dba {
+    type Result = ServiceResult<usize>;
+
+    fn handle(&mut self, d: DelBookmark, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::bookmarks::dsl::*;
+        let conn: &PooledConn = &self.0.get()?;
+        let n = diesel::delete(
+                bookmarks
+                    .filter(uname.eq(&d.uname))
+                    .filter(item_id.eq(d.item_id))
+            )
+            .execute(conn)?;
+        Ok(n)
+    }
+}
+
+// =================================================================================
+// =================================================================================
+// Model
+// =================================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, Queryable, Insertable)]
+#[table_name = "bookmarks"]
+pub struct Bookmark {
+    pub uname: String,
+    pub item_id: i32,
+    pub note: String,
+    pub is_read: bool,
+    pub save_at: NaiveDateTime,
+}
+
+// a saved item w/ the note
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedItem {
+    pub bookmark: Bookmark,
+    pub item: Item,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct SavedMsg {
+    pub saved: Vec<SavedItem>,
+    pub count: i64,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ReadQuery {
+    pub read: Option<bool>,  // all if none
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SaveItem {
+    pub item_id: i32,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NewBookmark {
+    pub uname: String,
+    pub item_id: i32,
+    pub note: Option<String>,  // keep the note if none
+}
+
+fn check_note(note: &Option<String>) -> bool {
+    note.as_ref()
+        .map(|n| n.trim().chars().count() <= MAX_NOTE_LEN)
+        .unwrap_or(true)
+}
+
+impl NewBookmark {
+    fn save(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Bookmark> {
+        use crate::schema::bookmarks::dsl::*;
+        use crate::schema::items;
+        // not found, rather than a violated foreign key
+        items::table
+            .find(self.item_id)
+            .select(items::id)
+            .get_result::<i32>(conn)?;
+
+        let b = Bookmark {
+            uname: self.uname.clone(),
+            item_id: self.item_id,
+            note: self.note.as_deref().unwrap_or("").trim().to_owned(),
+            is_read: false,
+            save_at: Utc::now().naive_utc(),
+        };
+        let saved = conn.transaction::<Bookmark, diesel::result::Error, _>(|| {
+            diesel::insert_into(bookmarks)
+                .values(&b)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            let target = bookmarks
+                .filter(uname.eq(&b.uname))
+                .filter(item_id.eq(b.item_id));
+            match self.note {
+                Some(_) => diesel::update(target)
+                    .set(note.eq(&b.note))
+                    .get_result::<Bookmark>(conn),
+                None => target.get_result::<Bookmark>(conn),
+            }
+        })?;
+
+        Ok(saved)
+    }
+
+    fn validate(&self) -> ServiceResult<()> {
+        if check_note(&self.note) {
+            Ok(())
+        } else {
+            Err(ServiceError::BadRequest("Note Too Long".into()))
+        }
+    }
+}
+
+impl Message for NewBookmark {
+    type Result = ServiceResult<Bookmark>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryBookmarks {
+    pub uname: String,
+    pub read: Option<bool>,
+    pub perpage: i32,
+    pub page: i32,
+}
+
+impl QueryBookmarks {
+    pub fn get(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<SavedMsg> {
+        use crate::schema::bookmarks::dsl::*;
+        use crate::schema::items;
+
+        let mut counter = bookmarks
+            .filter(uname.eq(self.uname.clone()))
+            .into_boxed();
+        let mut query = bookmarks
+            .inner_join(items::table)
+            .filter(uname.eq(self.uname.clone()))
+            .into_boxed();
+        if let Some(r) = self.read {
+            counter = counter.filter(is_read.eq(r));
+            query = query.filter(is_read.eq(r));
+        }
+        let count: i64 = counter.count().get_result(conn)?;
+
+        let perpage = std::cmp::min(std::cmp::max(self.perpage, 1), 100) as i64;
+        let page = std::cmp::max(self.page - 1, 0) as i64;
+        let saved = query
+            .order(save_at.desc())
+            .limit(perpage)
+            .offset(perpage * page)
+            .load::<(Bookmark, Item)>(conn)?
+            .into_iter()
+            .map(|(bookmark, item)| SavedItem { bookmark, item })
+            .collect();
+
+        Ok(SavedMsg { saved, count })
+    }
+}
+
+impl Message for QueryBookmarks {
+    type Result = ServiceResult<SavedMsg>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, AsChangeset)]
+#[table_name = "bookmarks"]
+pub struct BookmarkChange {
+    pub note: Option<String>,   // not change if none
+    pub is_read: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct EditBookmark {
+    pub uname: String,
+    pub item_id: i32,
+    pub change: BookmarkChange,
+}
+
+impl EditBookmark {
+    fn update(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Bookmark> {
+        use crate::schema::bookmarks::dsl::*;
+        let ch = BookmarkChange {
+            note: self.change.note.as_ref().map(|n| n.trim().to_owned()),
+            ..self.change.clone()
+        };
+        let b = diesel::update(
+                bookmarks
+                    .filter(uname.eq(&self.uname))
+                    .filter(item_id.eq(self.item_id))
+            )
+            .set(&ch)
+            .get_result::<Bookmark>(conn)?;
+        Ok(b)
+    }
+
+    fn validate(&self) -> ServiceResult<()> {
+        let ch = &self.change;
+        let check = check_note(&ch.note)
+            && (ch.note.is_some() || ch.is_read.is_some());
+        if check {
+            Ok(())
+        } else {
+            Err(ServiceError::BadRequest("Invalid Change".into()))
+        }
+    }
+}
+
+impl Message for EditBookmark {
+    type Result = ServiceResult<Bookmark>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DelBookmark {
+    pub uname: String,
+    pub item_id: i32,
+}
+
+impl Message for DelBookmark {
+    type Result = ServiceResult<usize>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_bookmark() {
+        let mut nb = NewBookmark {
+            uname: "toplog".to_owned(),
+            item_id: 1,
+            note: None,
+        };
+        assert!(nb.validate().is_ok());
+        nb.note = Some("ü".repeat(MAX_NOTE_LEN));
+        assert!(nb.validate().is_ok());
+        nb.note = Some("a".repeat(MAX_NOTE_LEN + 1));
+        assert!(nb.validate().is_err());
+
+        let mut eb = EditBookmark {
+            uname: "toplog".to_owned(),
+            item_id: 1,
+            change: BookmarkChange { note: None, is_read: None },
+        };
+        // nothing to change
+        assert!(eb.validate().is_err());
+        eb.change.is_read = Some(true);
+        assert!(eb.validate().is_ok());
+    }
+}
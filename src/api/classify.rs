@@ -0,0 +1,185 @@
+// api.classify: keyword rules to classify the topic and type of bot items
+
+use actix::{Handler, Message};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error, HttpResponse, ResponseError,
+    Result,
+};
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+use log::error;
+
+use crate::errors::{ServiceError, ServiceResult};
+use crate::api::auth::CheckCan;
+use crate::bot::classify::is_valid_target;
+use crate::{Dba, DbAddr, PooledConn};
+use crate::schema::{classify_rules};
+
+// POST: /api/classify-rules
+//
+// new rule, or update the weight if the same kw-kind-target exists
+pub async fn new(
+    rule: Json<NewClassifyRule>,
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let r = rule.into_inner();
+
+    if let Err(e) = r.validate() {
+        error!("{}", e);
+        return Ok(e.error_response());
+    }
+
+    let res = db.send(r).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<NewClassifyRule> for Dba {
+    type Result = ServiceResult<ClassifyRule>;
+
+    fn handle(&mut self, nr: NewClassifyRule, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        nr.new(conn)
+    }
+}
+
+// GET: /api/classify-rules
+//
+pub async fn get_list(
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let res = db.send(QueryClassifyRules).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<QueryClassifyRules> for Dba {
+    type Result = ServiceResult<Vec<ClassifyRule>>;
+
+    fn handle(&mut self, _q: QueryClassifyRules, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        Ok(ClassifyRule::load_all(conn)?)
+    }
+}
+
+// DELETE: /api/classify-rules/{id}
+//
+pub async fn del(
+    qr: Path<i32>,
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let res = db.send(DelClassifyRule{ id: qr.into_inner() }).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r.id)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<DelClassifyRule> for Dba {
+    type Result = ServiceResult<ClassifyRule>;
+
+    fn handle(&mut self, d: DelClassifyRule, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        use crate::schema::classify_rules::dsl::{classify_rules, id};
+        let rule = diesel::delete(classify_rules.filter(id.eq(d.id)))
+            .get_result::<ClassifyRule>(conn)?;
+        Ok(rule)
+    }
+}
+
+
+// =================================================================================
+// =================================================================================
+// Model
+// =================================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, Identifiable, Queryable)]
+#[table_name = "classify_rules"]
+pub struct ClassifyRule {
+    pub id: i32,
+    pub kw: String,      // a word or phrase, case-insensitive
+    pub kind: String,    // topic|ty
+    pub target: String,  // a topic in TOPIC_VEC, or a type in TY_VEC
+    pub weight: i32,
+}
+
+impl ClassifyRule {
+    pub fn load_all(conn: &PgConnection) -> QueryResult<Vec<ClassifyRule>> {
+        use crate::schema::classify_rules::dsl::*;
+        classify_rules
+            .order((kind.asc(), target.asc(), kw.asc()))
+            .load::<ClassifyRule>(conn)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default, Insertable)]
+#[table_name = "classify_rules"]
+pub struct NewClassifyRule {
+    pub kw: String,
+    pub kind: String,
+    pub target: String,
+    pub weight: i32,
+}
+
+impl NewClassifyRule {
+    fn new(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<ClassifyRule> {
+        use crate::schema::classify_rules::dsl::*;
+        let new_rule = NewClassifyRule {
+            kw: self.kw.trim().to_lowercase(),
+            kind: self.kind.trim().to_owned(),
+            target: self.target.trim().to_owned(),
+            weight: self.weight,
+        };
+        let rule = diesel::insert_into(classify_rules)
+            .values(&new_rule)
+            .on_conflict((kw, kind, target))
+            .do_update()
+            .set(weight.eq(new_rule.weight))
+            .get_result::<ClassifyRule>(conn)?;
+
+        Ok(rule)
+    }
+
+    fn validate(&self) -> ServiceResult<()> {
+        let check = self.kw.trim().len() > 0
+            && is_valid_target(self.kind.trim(), self.target.trim());
+
+        if check {
+            Ok(())
+        } else {
+            error!("classify rule");
+            Err(ServiceError::BadRequest("Invalid Keyword or Target".into()))
+        }
+    }
+}
+
+impl Message for NewClassifyRule {
+    type Result = ServiceResult<ClassifyRule>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryClassifyRules;
+
+impl Message for QueryClassifyRules {
+    type Result = ServiceResult<Vec<ClassifyRule>>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DelClassifyRule {
+    pub id: i32,
+}
+
+impl Message for DelClassifyRule {
+    type Result = ServiceResult<ClassifyRule>;
+}
@@ -0,0 +1,206 @@
+// api.crawl: crawl log of bot and the failing sources report
+
+use actix::{Handler, Message};
+use actix_web::{
+    web::{Data, Query},
+    Error, HttpResponse, ResponseError,
+    Result,
+};
+use diesel::prelude::*;
+use diesel::{self, dsl::any, ExpressionMethods, QueryDsl, RunQueryDsl};
+use chrono::{NaiveDateTime, Utc};
+use log::error;
+use std::collections::HashMap;
+
+use crate::errors::{ServiceError, ServiceResult};
+use crate::api::{auth::CheckCan, source::Source};
+use crate::bot::spider::WebPage;
+use crate::{Dba, DbAddr, PooledConn};
+use crate::schema::{crawl_logs};
+
+#[derive(Deserialize, Clone)]
+pub struct RunsQuery {
+    runs: Option<i64>,
+}
+
+// GET: /api/crawl-report?runs=3
+//
+// the sources failed or found nothing in the last n runs
+pub async fn report(
+    rq: Query<RunsQuery>,
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let runs = rq.runs.unwrap_or(3).max(1).min(30);
+    let res = db.send(QueryCrawlReport{ runs }).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<QueryCrawlReport> for Dba {
+    type Result = ServiceResult<Vec<FailingSource>>;
+
+    fn handle(&mut self, q: QueryCrawlReport, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        q.get(conn)
+    }
+}
+
+
+// =================================================================================
+// =================================================================================
+// Model
+// =================================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, Identifiable, Queryable)]
+#[table_name = "crawl_logs"]
+pub struct CrawlLog {
+    pub id: i32,
+    pub run_at: NaiveDateTime,
+    pub kind: String,            // source|page
+    pub url: String,
+    pub source_id: Option<i32>,
+    pub status: i32,             // 0: failed w/o response
+    pub duration_ms: i32,
+    pub error: String,
+    pub links_found: i32,        // links or feed entries in source page
+    pub crawl_at: NaiveDateTime,
+}
+
+impl CrawlLog {
+    // failed, or got nothing from a modified page
+    pub fn is_bad(&self) -> bool {
+        let ok = self.status >= 200 && self.status < 300;
+        let not_modified = self.status == 304;
+        !(not_modified || (ok && self.links_found > 0))
+    }
+
+    // keep the recent logs only
+    pub fn prune(conn: &PgConnection, days: i64) -> QueryResult<usize> {
+        use crate::schema::crawl_logs::dsl::{crawl_logs, crawl_at};
+        let before = Utc::now().naive_utc() - chrono::Duration::days(days);
+        diesel::delete(crawl_logs.filter(crawl_at.lt(before))).execute(conn)
+    }
+}
+
+// days to keep the crawl logs
+pub const LOG_KEEP_DAYS: i64 = 30;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Insertable)]
+#[table_name = "crawl_logs"]
+pub struct NewCrawlLog {
+    pub run_at: NaiveDateTime,
+    pub kind: String,
+    pub url: String,
+    pub source_id: Option<i32>,
+    pub status: i32,
+    pub duration_ms: i32,
+    pub error: String,
+    pub links_found: i32,
+}
+
+impl NewCrawlLog {
+    pub fn from_page(
+        page: &WebPage,
+        run_at: NaiveDateTime,
+        kind: &str,
+        source_id: Option<i32>,
+        links_found: usize,
+    ) -> Self {
+        NewCrawlLog {
+            run_at,
+            kind: kind.to_owned(),
+            url: page.url.clone(),
+            source_id,
+            status: page.status as i32,
+            duration_ms: page.duration_ms,
+            error: page.error.clone(),
+            links_found: links_found as i32,
+        }
+    }
+
+    pub fn save_all(
+        logs: &[NewCrawlLog],
+        conn: &PgConnection,
+    ) -> QueryResult<usize> {
+        use crate::schema::crawl_logs::dsl::{crawl_logs};
+        if logs.len() == 0 {
+            return Ok(0);
+        }
+        diesel::insert_into(crawl_logs)
+            .values(logs)
+            .execute(conn)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FailingSource {
+    pub source: Source,
+    pub logs: Vec<CrawlLog>,  // the last n, newest first
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryCrawlReport {
+    pub runs: i64,
+}
+
+impl QueryCrawlReport {
+    fn get(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Vec<FailingSource>> {
+        use crate::schema::crawl_logs::dsl::*;
+        let run_list: Vec<NaiveDateTime> = crawl_logs
+            .filter(kind.eq("source"))
+            .select(run_at)
+            .distinct()
+            .order(run_at.desc())
+            .limit(self.runs)
+            .load::<NaiveDateTime>(conn)?;
+        if (run_list.len() as i64) < self.runs {
+            return Ok(Vec::new());
+        }
+
+        let log_list = crawl_logs
+            .filter(kind.eq("source"))
+            .filter(run_at.eq(any(&run_list)))
+            .order(run_at.desc())
+            .load::<CrawlLog>(conn)?;
+        let mut logs_map: HashMap<i32, Vec<CrawlLog>> = HashMap::new();
+        for log in log_list {
+            if let Some(sid) = log.source_id {
+                logs_map.entry(sid).or_insert_with(Vec::new).push(log);
+            }
+        }
+
+        // bad in each of the n runs
+        let bad_ids: Vec<i32> = logs_map
+            .iter()
+            .filter(|(_, logs)| {
+                logs.len() as i64 >= self.runs && logs.iter().all(|l| l.is_bad())
+            })
+            .map(|(sid, _)| *sid)
+            .collect();
+
+        use crate::schema::sources::dsl::{sources, id as src_id};
+        let src_list = sources
+            .filter(src_id.eq(any(&bad_ids)))
+            .order(src_id.asc())
+            .load::<Source>(conn)?;
+
+        let failings = src_list
+            .into_iter()
+            .map(|s| {
+                let logs = logs_map.remove(&s.id).unwrap_or_default();
+                FailingSource { source: s, logs }
+            })
+            .collect();
+        Ok(failings)
+    }
+}
+
+impl Message for QueryCrawlReport {
+    type Result = ServiceResult<Vec<FailingSource>>;
+}
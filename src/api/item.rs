@@ -11,10 +11,11 @@ use diesel::prelude::*;
 use diesel::{self, dsl::any, ExpressionMethods, QueryDsl, RunQueryDsl};
 use chrono::{NaiveDateTime, NaiveDate, Utc};
 use log::error;
+use std::collections::HashMap;
 
 use crate::errors::{ServiceError, ServiceResult};
 use crate::api::{
-    ReqQuery, ActionQuery, 
+    ReqQuery, PageQuery, ActionQuery, ItemsMsg,
     auth::{CheckUser, CheckCan, CheckCsrf},
     re_test_url,
 };
@@ -81,11 +82,15 @@ impl Handler<SpiderItem> for Dba {
 // 
 pub async fn update(
     item: Json<UpdateItem>,
-    _auth: CheckUser,
+    auth: CheckUser,
     _csrf: CheckCsrf,
     db: Data<DbAddr>,
 ) -> ServiceResult<HttpResponse> {
-    let res = db.send(item.into_inner()).await?;
+    // the editor, recorded in revisions
+    let mut up = item.into_inner();
+    up.post_by = auth.uname;
+
+    let res = db.send(up).await?;
     match res {
         Ok(b) => Ok(HttpResponse::Ok().json(b)),
         Err(e) => { error!("{}", e); Ok(e.error_response()) },
@@ -103,14 +108,15 @@ impl Handler<UpdateItem> for Dba {
 
 // GET: /api/items/{id}
 // 
+// w/ the vote of caller if signed in
 pub async fn get(
     qb: Path<i32>,
+    auth: Option<CheckUser>,
     db: Data<DbAddr>,
 ) -> ServiceResult<HttpResponse> {
-    let item = QueryItem{
+    let item = QueryVoted{
         id: qb.into_inner(), 
-        method: String::from("GET"),
-        uname: String::new()
+        uname: auth.map(|a| a.uname),
     };
     let res = db.send(item).await?;
     match res {
@@ -119,6 +125,59 @@ pub async fn get(
     }
 }
 
+impl Handler<QueryVoted> for Dba {
+    type Result = ServiceResult<VotedItem>;
+
+    fn handle(&mut self, qv: QueryVoted, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        qv.get(conn)
+    }
+}
+
+// POST: /api/items/{id}/click
+// 
+// the link opened, for hot ranking, count once per user
+pub async fn click(
+    p: Path<i32>,
+    auth: CheckUser,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let ci = ClickItem{ id: p.into_inner(), uname: auth.uname };
+    let res = db.send(ci).await?;
+    match res {
+        Ok(n) => Ok(HttpResponse::Ok().json(n)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<ClickItem> for Dba {
+    type Result = ServiceResult<i32>;
+
+    fn handle(&mut self, ci: ClickItem, _: &mut Self::Context) -> Self::Result {
+        use crate::schema::items::dsl::*;
+        use crate::schema::clickitems;
+        let conn: &PooledConn = &self.0.get()?;
+        let n = conn.transaction::<i32, diesel::result::Error, _>(|| {
+            let cur = items.find(ci.id).select(clicks).get_result::<i32>(conn)?;
+            let new_click = diesel::insert_into(clickitems::table)
+                .values((
+                    clickitems::uname.eq(&ci.uname),
+                    clickitems::item_id.eq(ci.id),
+                ))
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            if new_click == 0 {
+                return Ok(cur);  // clicked before
+            }
+            diesel::update(items.find(ci.id))
+                .set(clicks.eq(clicks + 1))
+                .returning(clicks)
+                .get_result::<i32>(conn)
+        })?;
+        Ok(n)
+    }
+}
+
 // PATCH: /api/items/{id}
 // 
 pub async fn toggle_top(
@@ -138,26 +197,43 @@ pub async fn toggle_top(
     }
 }
 
-// PUT: /api/items/{id}?action=vote|veto
+// PUT: /api/items/{id}?action=vote|veto|clear
 // 
+// vote again on the other switches, clear withdraws
 pub async fn vote_or_veto(
     qb: Path<i32>,
     aq: Query<ActionQuery>,
     auth: CheckUser,
     db: Data<DbAddr>,
 ) -> ServiceResult<HttpResponse> {
-    let item = QueryItem{
+    let act = match VoteAct::parse(&aq.action) {
+        Some(a) => a,
+        None => {
+            let e = ServiceError::BadRequest("Invalid Action".into());
+            return Ok(e.error_response());
+        }
+    };
+    let vi = VoteOnItem{
         id: qb.into_inner(), 
-        method: aq.action.to_uppercase(),
-        uname: auth.uname
+        uname: auth.uname,
+        act,
     };
-    let res = db.send(item).await?;
+    let res = db.send(vi).await?;
     match res {
-        Ok(b) => Ok(HttpResponse::Ok().json(b.vote)),
+        Ok(b) => Ok(HttpResponse::Ok().json(b)),
         Err(e) => { error!("{}", e); Ok(e.error_response()) },
     }
 }
 
+impl Handler<VoteOnItem> for Dba {
+    type Result = ServiceResult<VotedItem>;
+
+    fn handle(&mut self, vi: VoteOnItem, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        vi.vote(conn)
+    }
+}
+
 // DELETE: /api/items/{id}
 // 
 pub async fn del(
@@ -188,53 +264,193 @@ impl Handler<QueryItem> for Dba {
         match method {
             "GET" => { qb.get(conn) }
             "PATCH" => { qb.toggle_top(conn) }
-            "VOTE" => { qb.vote_or_veto(conn, "VOTE") }
-            "VETO" => { qb.vote_or_veto(conn, "VETO") }
             "DELETE" => { qb.del(conn) }
+            "MERGE" => { qb.merge_dup(conn) }
+            "KEEP" => { qb.keep_dup(conn) }
             _ => { qb.get(conn) },
         }
     }
 }
 
-// GET: api/getitems/{pper}?per=topic|author&kw=&page=p&perpage=42
+// GET: /api/dups
+// 
+// the items flagged as likely duplicate, w/ the origin
+pub async fn get_dups(
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let res = db.send(QueryDups).await?;
+    match res {
+        Ok(b) => Ok(HttpResponse::Ok().json(b)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<QueryDups> for Dba {
+    type Result = ServiceResult<Vec<DupItem>>;
+
+    fn handle(&mut self, qd: QueryDups, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        qd.get(conn)
+    }
+}
+
+// PUT: /api/dups/{id}?action=merge|keep
+// 
+// merge into the origin, or keep as not a duplicate
+pub async fn handle_dup(
+    qb: Path<i32>,
+    aq: Query<ActionQuery>,
+    auth: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let action = aq.action.to_uppercase();
+    if action != "MERGE" && action != "KEEP" {
+        return Ok(ServiceError::BadRequest("Invalid Action".into()).error_response());
+    }
+    let item = QueryItem{
+        id: qb.into_inner(), 
+        method: action,
+        uname: auth.uname
+    };
+    let res = db.send(item).await?;
+    match res {
+        Ok(b) => Ok(HttpResponse::Ok().json(b)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+// GET: /api/pending
+// 
+// the bot items waiting for review
+pub async fn get_pending(
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let res = db.send(QueryPending).await?;
+    match res {
+        Ok(b) => Ok(HttpResponse::Ok().json(b)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<QueryPending> for Dba {
+    type Result = ServiceResult<Vec<Item>>;
+
+    fn handle(&mut self, qp: QueryPending, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        qp.get(conn)
+    }
+}
+
+// PUT: /api/pending
+// 
+// in bulk: {action: approve|reject|edit, ids, topic?, ty?}
+pub async fn moderate(
+    mi: Json<ModerateItems>,
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let m = mi.into_inner();
+
+    if let Err(e) = m.validate() {
+        error!("{}", e);
+        return Ok(e.error_response());
+    }
+
+    let res = db.send(m).await?;
+    match res {
+        Ok(n) => Ok(HttpResponse::Ok().json(n)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<ModerateItems> for Dba {
+    type Result = ServiceResult<usize>;
+
+    fn handle(&mut self, m: ModerateItems, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        match m.action.to_uppercase().as_str() {
+            "APPROVE" => m.approve(conn),
+            "REJECT" => m.reject(conn),
+            _ => m.edit(conn),
+        }
+    }
+}
+
+// GET: /api/items?topic=&ty=&author=&host=&post_by=&voted_by=&since=&until=&top=&sort=
+//       &perpage=42&page=|after=|before=
 // 
+// w/ my_votes if signed in
+pub async fn get_items(
+    fq: Query<ItemFilter>,
+    pq: Query<PageQuery>,
+    auth: Option<CheckUser>,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let filter = fq.into_inner();
+
+    if let Err(e) = filter.validate() {
+        error!("{}", e);
+        return Ok(e.error_response());
+    }
+
+    let page = PageArgs::new(pq.perpage.unwrap_or(42), pq.page.unwrap_or(1))
+        .cursor(pq.after.as_deref(), pq.before.as_deref());
+    let uname = auth.map(|a| a.uname);
+    let res = db.send(QueryItems { filter, page, uname }).await?;
+    match res {
+        Ok(b) => Ok(HttpResponse::Ok().json(b)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+// GET: api/getitems/{pper}?per=topic|author&kw=&page=p&perpage=42&after=|before=
+// 
+// as /api/items w/ the filter
 pub async fn get_list(
     pt: Path<String>,
     pq: Query<ReqQuery>,
+    auth: Option<CheckUser>,
     db: Data<DbAddr>,
 ) -> ServiceResult<HttpResponse> {
     let p = pt.into_inner();
     // extract query param
-    let perpage = pq.perpage;
-    let page = pq.page;
+    let page = PageArgs::new(pq.perpage, pq.page)
+        .cursor(pq.after.as_deref(), pq.before.as_deref());
     let kw = pq.clone().kw;
     let per = pq.clone().per;
-    let item = match p.trim() {
-        "topic" => QueryItems::Topic(kw, perpage, page),
-        "author" => QueryItems::Author(kw, perpage, page),
-        "ty" => QueryItems::Ty(kw, perpage, page),
-        "index" => QueryItems::Index(kw, perpage, page),
-        "user" => QueryItems::User(per, kw, perpage, page),
+    let mut filter = ItemFilter::default();
+    match p.trim() {
+        "topic" => {
+            filter.topic = Some(kw);
+            filter.top = Some(true);
+        }
+        "author" => filter.author = Some(kw),
+        "ty" => {
+            filter.ty = Some(kw);
+            filter.top = Some(true);
+        }
+        "index" => filter = ItemFilter::of_collection("all", &kw),
+        "user" => match kw.trim() {
+            "submit" => filter.post_by = Some(per),
+            "vote" => filter.voted_by = Some(per),
+            _ => return Ok(HttpResponse::Ok().json(ItemsMsg::default())),
+        }
         // other: 
         // kw-topic: rust|go.., per-ty: art|book|..
-        _ => QueryItems::Tt(kw, per, perpage, page),
+        _ => filter = ItemFilter::of_collection(&kw, &per),
     };
-    let res = db.send(item).await?;
+    let uname = auth.map(|a| a.uname);
+    let res = db.send(QueryItems { filter, page, uname }).await?;
     match res {
-        Ok(b) => {
-            use crate::api::ItemsMsg;
-            let res = ItemsMsg {
-                items: b.0,
-                count: b.1,
-            };
-            Ok(HttpResponse::Ok().json(res))
-        },
+        Ok(b) => Ok(HttpResponse::Ok().json(b)),
         Err(e) => { error!("{}", e); Ok(e.error_response()) },
     }
 }
 
 impl Handler<QueryItems> for Dba {
-    type Result = ServiceResult<(Vec<Item>, i64)>;
+    type Result = ServiceResult<ItemsMsg>;
 
     fn handle(&mut self, qbs: QueryItems, _: &mut Self::Context) -> Self::Result {
         let conn: &PooledConn = &self.0.get()?;
@@ -266,8 +482,70 @@ pub struct Item {
     pub post_at: NaiveDateTime,
     pub is_top: bool,
     pub vote: i32,
+    pub simhash: i64,         // fingerprint of title and content
+    pub dup_of: Option<i32>,  // likely a duplicate of, hidden if some
+    pub is_dead: bool,        // link dead in a few checks in a row
+    pub is_pending: bool,     // bot item waiting for review, hidden if true
+    pub clicks: i32,          // times the link opened
+    pub canonical_link: String,  // the key to dedup, link kept as is
+}
+
+impl Item {
+    // fingerprint the new item, and flag it if a near-duplicate of 
+    // an earlier one, e.g. cross-posted on another host
+    pub fn mark_dup(self, conn: &PgConnection) -> QueryResult<Item> {
+        let mut marked = Item::mark_dups(vec![self], conn)?;
+        Ok(marked.remove(0))
+    }
+
+    // the same for a batch, e.g. of a crawl, the candidates loaded once
+    pub fn mark_dups(new_items: Vec<Item>, conn: &PgConnection) -> QueryResult<Vec<Item>> {
+        use crate::schema::items::dsl::*;
+        use crate::util::simhash::{item_fingerprint, is_near_dup};
+        let fps: Vec<i64> = new_items
+            .iter()
+            .map(|i| item_fingerprint(&i.title, &i.content))
+            .collect();
+        if fps.iter().all(|fp| *fp == 0) {
+            return Ok(new_items);
+        }
+
+        let new_ids: Vec<i32> = new_items.iter().map(|i| i.id).collect();
+        let since = Utc::now().naive_utc() - chrono::Duration::days(DUP_LOOKBACK);
+        let candidates: Vec<(i32, i64)> = items
+            .filter(simhash.ne(0))
+            .filter(dup_of.is_null())
+            .filter(is_pending.eq(false))  // not hide both while in review
+            .filter(post_at.gt(since))
+            .order(id.asc())
+            .select((id, simhash))
+            .load::<(i32, i64)>(conn)?
+            .into_iter()
+            .filter(|(i, _)| !new_ids.contains(i))
+            .collect();
+
+        let mut marked = Vec::with_capacity(new_items.len());
+        for (itm, fp) in new_items.into_iter().zip(fps) {
+            if fp == 0 {
+                marked.push(itm);
+                continue;
+            }
+            let origin = candidates
+                .iter()
+                .find(|(_, h)| is_near_dup(fp, *h))
+                .map(|(i, _)| *i);
+            let itm = diesel::update(&itm)
+                .set((simhash.eq(fp), dup_of.eq(origin)))
+                .get_result::<Item>(conn)?;
+            marked.push(itm);
+        }
+        Ok(marked)
+    }
 }
 
+// days to look back for the duplicate
+pub const DUP_LOOKBACK: i64 = 180;
+
 #[derive(Clone, Debug, Serialize, Deserialize, Insertable)]
 #[table_name = "items"]
 pub struct NewItem {
@@ -280,6 +558,10 @@ pub struct NewItem {
     pub link: String,
     pub post_by: String,
     pub pub_at: NaiveDate,
+    #[serde(default)]
+    pub is_pending: bool,
+    #[serde(default)]
+    pub canonical_link: String,  // of link, set on save
 }
 
 impl NewItem {
@@ -287,10 +569,11 @@ impl NewItem {
         self, 
         conn: &PooledConn,
     ) -> ServiceResult<Item> {
-        use crate::schema::items::dsl::{items, link};
+        use crate::schema::items::dsl::{items, link, canonical_link, dup_of};
+        use crate::util::helper::canonical_url;
         let title = self.title.trim();
-        let nlink = self.link.trim();
-        let ilink = nlink.to_string();
+        let ilink = self.link.trim().to_owned();
+        let c_link = canonical_url(&ilink);
         let new_item = NewItem {
             title: title.to_owned(),
             content: self.content.trim().to_owned(),  // do some trim
@@ -301,6 +584,8 @@ impl NewItem {
             link: ilink.clone(),
             post_by: self.post_by.trim().to_owned(),
             pub_at: self.pub_at,
+            is_pending: false,
+            canonical_link: c_link.clone(),
         };
 
         // save item's author to blog, for reference
@@ -316,9 +601,11 @@ impl NewItem {
             .get_result::<Item>(conn);
         
         let mut item_new = if let Ok(itm) = try_save_new_item {
-                itm
+                itm.mark_dup(conn)?
         } else {
-            items.filter(link.eq(&ilink))
+            // the listed one, as unique
+            items.filter(canonical_link.eq(&c_link))
+                .filter(dup_of.is_null())
                 .get_result::<Item>(conn)?
         };
 
@@ -327,14 +614,20 @@ impl NewItem {
                 dotenv::var("DOMAIN_HOST").unwrap_or(String::from("https://toplog.cc/"))
                 + "item/" + &item_new.id.to_string();
             item_new = diesel::update(&item_new)
-                .set(link.eq(itmlink))
+                .set((
+                    canonical_link.eq(canonical_url(&itmlink)),
+                    link.eq(itmlink),
+                ))
                 .get_result::<Item>(conn)?;
         }
 
-        // save new link to json
-        use crate::util::helper::{serde_add_links};
-        let link_vec = vec!(ilink);
-        serde_add_links(link_vec);
+        // save new link as seen by bot
+        use crate::bot::seen::add_urls;
+        add_urls(conn, &[c_link]).unwrap_or(0);  // ignore potential error
+
+        // a local thumbnail of logo, in background
+        use crate::bot::tasks::queue_item_thumb;
+        queue_item_thumb(conn, item_new.id);
 
         // ========================================================
         let itm = item_new.clone();
@@ -373,15 +666,17 @@ pub struct UpdateItem {
 }
 
 impl UpdateItem {
-    fn update(
+    pub(crate) fn update(
         mut self, 
         conn: &PooledConn,
     ) -> ServiceResult<Item> {
         use crate::schema::items::dsl::*;
         let old = items.filter(id.eq(self.id))
             .get_result::<Item>(conn)?;
+        let editor = self.post_by.clone();
         
         // check if anything changed
+        use crate::util::helper::canonical_url;
         let old_link = old.link.trim();
         let new_title = self.title.trim();
         let new_content = self.content.trim();
@@ -406,19 +701,25 @@ impl UpdateItem {
         }
         
         let ilink = if new_link.len() > 0 {
-            // check link if existing
-            let check_link = items.filter(link.eq(new_link))
-                .select(link)
-                .get_result::<String>(conn);
-            match check_link {
-                Ok(l) => l,
-                _ => new_link.to_string()
-            }            
+            new_link.to_string()
         } else {
             let base = dotenv::var("DOMAIN_HOST")
                 .unwrap_or(String::from("https://toplog.cc/"));
             base + "item/" + &self.id.to_string()
         };
+        let c_link = canonical_url(&ilink);
+        // check link if existing, as another item's
+        let link_taken = items
+            .filter(canonical_link.eq(&c_link).and(id.ne(self.id)))
+            .select(id)
+            .first::<i32>(conn)
+            .optional()?
+            .is_some();
+        if link_taken {
+            error!("link existing");
+            return Err(ServiceError::BadRequest("Link Existing".to_owned()));
+        }
+        let logo_changed = new_logo != old.logo.trim();
         // post_by
         let postBy = 
             if &old.post_by == "bot" { &self.post_by } else { &old.post_by};
@@ -443,15 +744,26 @@ impl UpdateItem {
             NewBlog::save_name_as_blog(aname, conn);  // ignore potential error
         }
 
-        let item_update = diesel::update(&old)
-            .set(up)
-            .get_result::<Item>(conn)?;
+        // keep the previous version in revisions
+        use crate::api::revision::ItemRevision;
+        let item_update = conn.transaction::<Item, diesel::result::Error, _>(|| {
+            let itm = diesel::update(&old)
+                .set((up, canonical_link.eq(&c_link)))
+                .get_result::<Item>(conn)?;
+            ItemRevision::record(conn, &old, &itm, &editor)?;
+            Ok(itm)
+        })?;
 
-        // save new link to json
+        // save new link as seen by bot
         if new_link.len() > 0 && new_link != old_link {
-            use crate::util::helper::{serde_add_links};
-            let link_vec = vec!(new_link.to_string());
-            serde_add_links(link_vec);
+            use crate::bot::seen::add_urls;
+            add_urls(conn, &[c_link]).unwrap_or(0);  // ignore potential error
+        }
+
+        // a local thumbnail of the new logo, in background
+        if logo_changed {
+            use crate::bot::tasks::queue_item_thumb;
+            queue_item_thumb(conn, item_update.id);
         }
 
         // ======================================================
@@ -493,16 +805,32 @@ impl SpiderItem {
         &self, 
         conn: &PooledConn,
     ) -> ServiceResult<Item> {
-        use crate::bot::spider::{WebPage};
-        use crate::schema::items::dsl::{items, link};
+        use crate::bot::spider::{WebPage, get_author_topic};
+        use crate::schema::items::dsl::{items, canonical_link, dup_of};
+        use crate::util::helper::canonical_url;
         let sp = self.clone();
-        let ilink = self.url.trim();
-        let sp_item = WebPage::new(&ilink)?.into_item();
+        let page = WebPage::new(self.url.trim())?;
+        let (host_author, host_topic) = get_author_topic(page.domain.trim());
+        let (sp_item, keywords) = page.into_item_kw(&host_author, &host_topic);
+        // the canonical link in page, or the url submitted
+        let ilink = if sp_item.link.trim().len() > 0 {
+            sp_item.link.trim().to_owned()
+        } else {
+            self.url.trim().to_owned()
+        };
+        let c_link = canonical_url(&ilink);
 
+        // classify if not picked, else the default
+        use crate::bot::classify::{Classifier, with_keywords};
+        let classifier = Classifier::load(conn)?;
+        let title = &sp_item.title;
+        let content = &with_keywords(&sp_item.content, &keywords);
         let sp_topic = sp.topic;
         use crate::view::{TY_VEC};
         let topic = if sp_topic.trim() == "all" || sp_topic.trim() == "from" {
-            String::from("Rust")
+            classifier
+                .topic_of(title, content, &ilink)
+                .unwrap_or(String::from("Rust"))
         } else {
             sp_topic
         };
@@ -510,11 +838,15 @@ impl SpiderItem {
         let ty = if TY_VEC.contains(&sp_ty.trim()) {
             sp_ty
         } else {
-            String::from("Article")
+            classifier
+                .ty_of(title, content, &ilink)
+                .unwrap_or(String::from("Article"))
         }; 
         let item_new = NewItem {
             topic: topic.clone(),
             ty,
+            link: ilink,
+            canonical_link: c_link.clone(),
             ..sp_item
         };
         // save to db
@@ -524,16 +856,21 @@ impl SpiderItem {
             .get_result::<Item>(conn);
         
         let new_item = if let Ok(itm) = try_save_new_item {
-                itm
+                itm.mark_dup(conn)?
         } else {
-            items.filter(link.eq(ilink))
+            // the listed one, as unique
+            items.filter(canonical_link.eq(&c_link))
+                .filter(dup_of.is_null())
                 .get_result::<Item>(conn)?
         };
 
-        // save new link to json
-        use crate::util::helper::{serde_add_links};
-        let link_vec = vec!(ilink.to_string());
-        serde_add_links(link_vec);
+        // save new link as seen by bot
+        use crate::bot::seen::add_urls;
+        add_urls(conn, &[c_link]).unwrap_or(0);  // ignore potential error
+
+        // a local thumbnail of logo, in background
+        use crate::bot::tasks::queue_item_thumb;
+        queue_item_thumb(conn, new_item.id);
         
         // ==================================
         // del related html
@@ -568,7 +905,7 @@ impl Message for SpiderItem {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct QueryItem {
     pub id: i32,
-    pub method: String, // get|put|delete|vote
+    pub method: String, // get|patch|delete|merge|keep
     pub uname: String,
 }
 
@@ -583,46 +920,6 @@ impl QueryItem {
         Ok(item)
     }
 
-    fn vote_or_veto(
-        &self, 
-        conn: &PooledConn,
-        action: &str,
-    ) -> ServiceResult<Item> {
-        use crate::schema::items::dsl::{items, id, vote, is_top};
-
-        let old = items
-            .filter(id.eq(&self.id))
-            .get_result::<Item>(conn)?;
-        let old_vote = old.vote;
-        let old_is_top = old.is_top;
-        let act = action.to_uppercase();
-
-        use crate::schema::voteitems::dsl::{voteitems};
-        let itemid = old.id;
-        let new_vote = VoteItem {
-            uname: self.uname.to_owned(),
-            item_id: itemid,
-            vote_at: Utc::now().naive_utc(),
-            vote_as: if act == "VOTE" { 1 } else { -1 },
-        };
-        let as_vote = new_vote.new(conn).unwrap_or(0) as i32;
-
-        let incr = if act == "VOTE" { as_vote } else { 0 - as_vote };
-        let threshold: i32 = dotenv::var("THRESHOLD")
-            .unwrap_or("42".to_owned())
-            .parse().unwrap_or(42);
-        let if_top = old_vote > threshold || old_is_top;
-
-        let item = diesel::update(&old)
-            .set((
-                vote.eq(vote + incr),
-                is_top.eq(if_top)
-            ))
-            .get_result::<Item>(conn)?;
-
-        Ok(item)
-    }
-
     fn toggle_top(
         &self, 
         conn: &PooledConn,
@@ -672,199 +969,907 @@ impl QueryItem {
             .get_result::<Item>(conn)?;
         Ok(item)
     }
+
+    // move the votes, bookmarks and clicks to the origin, then del the duplicate
+    fn merge_dup(
+        &self, 
+        conn: &PooledConn,
+    ) -> ServiceResult<Item> {
+        use crate::schema::items::dsl::{items, id, vote, is_top, clicks};
+        use crate::schema::voteitems::dsl::{voteitems, item_id, uname};
+        let dup = items
+            .filter(id.eq(&self.id))
+            .get_result::<Item>(conn)?;
+        let origin_id = match dup.dup_of {
+            Some(i) => i,
+            None => return Err(ServiceError::BadRequest("Not a duplicate".into())),
+        };
+
+        let origin = conn.transaction::<Item, diesel::result::Error, _>(|| {
+            let origin = items
+                .filter(id.eq(origin_id))
+                .get_result::<Item>(conn)?;
+            // the votes on the duplicate only
+            let voted: Vec<String> = voteitems
+                .filter(item_id.eq(origin_id))
+                .select(uname)
+                .load::<String>(conn)?;
+            let moved: Vec<VoteItem> = voteitems
+                .filter(item_id.eq(dup.id))
+                .load::<VoteItem>(conn)?
+                .into_iter()
+                .filter(|v| !voted.contains(&v.uname))
+                .map(|v| VoteItem { item_id: origin_id, ..v })
+                .collect();
+            let incr: i32 = moved.iter().map(|v| v.vote_as as i32).sum();
+            diesel::insert_into(voteitems)
+                .values(&moved)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+
+            // saved by users, the note on the origin kept if both
+            use crate::api::bookmark::Bookmark;
+            use crate::schema::bookmarks;
+            let saved: Vec<Bookmark> = bookmarks::table
+                .filter(bookmarks::item_id.eq(dup.id))
+                .load::<Bookmark>(conn)?
+                .into_iter()
+                .map(|b| Bookmark { item_id: origin_id, ..b })
+                .collect();
+            diesel::insert_into(bookmarks::table)
+                .values(&saved)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+
+            // clicked by users, counted once on the origin
+            use crate::schema::clickitems;
+            let clickers: Vec<(String, NaiveDateTime)> = clickitems::table
+                .filter(clickitems::item_id.eq(dup.id))
+                .select((clickitems::uname, clickitems::click_at))
+                .load::<(String, NaiveDateTime)>(conn)?;
+            let clicked: Vec<_> = clickers
+                .iter()
+                .map(|(u, t)| (
+                    clickitems::uname.eq(u),
+                    clickitems::item_id.eq(origin_id),
+                    clickitems::click_at.eq(t),
+                ))
+                .collect();
+            diesel::insert_into(clickitems::table)
+                .values(&clicked)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+
+            let origin = diesel::update(&origin)
+                .set((
+                    vote.eq(vote + incr),
+                    clicks.eq(clicks + dup.clicks),
+                    is_top.eq(origin.is_top || dup.is_top),
+                ))
+                .get_result::<Item>(conn)?;
+            diesel::delete(&dup).execute(conn)?;
+
+            Ok(origin)
+        })?;
+
+        del_listed_html(&dup);
+        Ok(origin)
+    }
+
+    // not a duplicate
+    fn keep_dup(
+        &self, 
+        conn: &PooledConn,
+    ) -> ServiceResult<Item> {
+        use crate::schema::items::dsl::{items, id, dup_of, canonical_link};
+        // the same link as a listed one, merge instead
+        let dup = items.find(self.id).get_result::<Item>(conn)?;
+        let same_link = dup.canonical_link.len() > 0 && items
+            .filter(canonical_link.eq(&dup.canonical_link))
+            .filter(dup_of.is_null())
+            .filter(id.ne(self.id))
+            .select(id)
+            .first::<i32>(conn)
+            .optional()?
+            .is_some();
+        if same_link {
+            return Err(ServiceError::BadRequest("Same Link, Merge Instead".into()));
+        }
+        let item = diesel::update(items.filter(id.eq(&self.id)))
+            .set(dup_of.eq(None::<i32>))
+            .get_result::<Item>(conn)?;
+
+        del_listed_html(&item);
+        Ok(item)
+    }
+}
+
+// del related html, re-generate when visit
+pub(crate) fn del_listed_html(itm: &Item) {
+    let name1 = String::from("collection/") + &itm.topic + "-" + &itm.ty;
+    let name2 = String::from("collection/") + &itm.topic + "-newest";
+    let name3 = String::from("collection/all-") + &itm.ty;
+    del_html(&name1).unwrap_or(());
+    del_html(&name2).unwrap_or(());
+    del_html(&name3).unwrap_or(());
+    del_html("collection/all-index").unwrap_or(());
+    del_html("collection/all-newest").unwrap_or(());
+    del_html(&(String::from("item/") + &itm.id.to_string())).unwrap_or(());
 }
 
 impl Message for QueryItem {
     type Result = ServiceResult<Item>;
 }
 
+// an item w/ the vote of the caller: 1 vote, -1 veto, 0 none
 #[derive(Deserialize, Serialize, Debug, Clone)]
-pub enum QueryItems {
-    Index(String, i32, i32),
-    Topic(String, i32, i32),        // topic, perpage, page
-    User(String, String, i32, i32), // uname, action:submit|vote, 
-    Ty(String, i32, i32),           // ty, perpage, page
-    Tt(String, String, i32, i32),   // topic, ty, perpage, page
-    Author(String, i32, i32),       // aname, ..
+pub struct VotedItem {
+    #[serde(flatten)]
+    pub item: Item,
+    pub my_vote: i16,
 }
 
-impl QueryItems {
-    pub fn get(
-        self, 
+fn my_vote(
+    conn: &PgConnection, 
+    u: &str, 
+    itm: i32,
+) -> QueryResult<i16> {
+    use crate::schema::voteitems::dsl::{voteitems, uname, item_id, vote_as};
+    let v = voteitems
+        .filter(uname.eq(u))
+        .filter(item_id.eq(itm))
+        .select(vote_as)
+        .first::<i16>(conn)
+        .optional()?;
+    Ok(v.unwrap_or(0))
+}
+
+// the votes of the user on a page of items, one query, voted only
+pub fn my_votes(
+    conn: &PgConnection, 
+    u: &str, 
+    itms: &[i32],
+) -> QueryResult<HashMap<i32, i16>> {
+    use crate::schema::voteitems::dsl::{voteitems, uname, item_id, vote_as};
+    if itms.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let votes = voteitems
+        .filter(uname.eq(u))
+        .filter(item_id.eq(any(itms)))
+        .select((item_id, vote_as))
+        .load::<(i32, i16)>(conn)?;
+    Ok(votes.into_iter().filter(|(_, v)| *v != 0).collect())
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryVoted {
+    pub id: i32,
+    pub uname: Option<String>,
+}
+
+impl QueryVoted {
+    fn get(
+        &self, 
+        conn: &PooledConn,
+    ) -> ServiceResult<VotedItem> {
+        use crate::schema::items::dsl::items;
+        let item = items.find(self.id).get_result::<Item>(conn)?;
+        let my = match self.uname.as_ref() {
+            Some(u) => my_vote(conn, u, self.id)?,
+            None => 0,
+        };
+        Ok(VotedItem { item, my_vote: my })
+    }
+}
+
+impl Message for QueryVoted {
+    type Result = ServiceResult<VotedItem>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum VoteAct {
+    Vote,
+    Veto,
+    Clear,
+}
+
+impl VoteAct {
+    pub fn parse(action: &str) -> Option<Self> {
+        match action.trim().to_uppercase().as_str() {
+            "VOTE" => Some(VoteAct::Vote),
+            "VETO" => Some(VoteAct::Veto),
+            "CLEAR" => Some(VoteAct::Clear),
+            _ => None,
+        }
+    }
+
+    // from the vote before: the vote after, and the change of items.vote
+    pub fn apply(self, old: i16) -> (i16, i32) {
+        let new = match self {
+            VoteAct::Vote => 1,
+            VoteAct::Veto => -1,
+            VoteAct::Clear => 0,
+        };
+        (new, (new - old) as i32)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct VoteOnItem {
+    pub id: i32,
+    pub uname: String,
+    pub act: VoteAct,
+}
+
+impl VoteOnItem {
+    fn vote(
+        &self, 
+        conn: &PooledConn,
+    ) -> ServiceResult<VotedItem> {
+        use crate::schema::items::dsl::{items, vote, is_top};
+        let threshold: i32 = dotenv::var("THRESHOLD")
+            .unwrap_or("42".to_owned())
+            .parse().unwrap_or(42);
+
+        let voted = conn.transaction::<VotedItem, diesel::result::Error, _>(|| {
+            // lock the item, the concurrent votes in turn
+            let old = items.find(self.id)
+                .for_update()
+                .get_result::<Item>(conn)?;
+            let old_as = my_vote(conn, &self.uname, self.id)?;
+            let (new_as, incr) = self.act.apply(old_as);
+            if incr == 0 {
+                return Ok(VotedItem { item: old, my_vote: old_as });
+            }
+
+            let v = VoteItem {
+                uname: self.uname.clone(),
+                item_id: self.id,
+                vote_at: Utc::now().naive_utc(),
+                vote_as: new_as,
+            };
+            if new_as == 0 { v.del(conn)?; } else { v.save(conn)?; }
+
+            let if_top = old.is_top || old.vote + incr > threshold;
+            let item = diesel::update(&old)
+                .set((
+                    vote.eq(vote + incr),
+                    is_top.eq(if_top)
+                ))
+                .get_result::<Item>(conn)?;
+            Ok(VotedItem { item, my_vote: new_as })
+        })?;
+
+        Ok(voted)
+    }
+}
+
+impl Message for VoteOnItem {
+    type Result = ServiceResult<VotedItem>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DupItem {
+    pub item: Item,
+    pub origin: Option<Item>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryDups;
+
+impl QueryDups {
+    fn get(
+        &self, 
         conn: &PooledConn,
-    ) -> ServiceResult<(Vec<Item>, i64)> {
+    ) -> ServiceResult<Vec<DupItem>> {
         use crate::schema::items::dsl::*;
-        let mut item_list: Vec<Item> = Vec::new();
-        let mut item_count = 0; // currently no need
-        match self {
-            QueryItems::Index(typ, o, p) => {  // topic = all 
-                let p_o = std::cmp::max(0, p-1);
-                match typ.to_lowercase().trim() {
-                    "index" => {
-                        item_list = items
-                            .filter(is_top.eq(true))
-                            .order(post_at.desc())
-                            .limit(o.into())
-                            .offset((o * p_o).into())
-                            .load::<Item>(conn)?;
-                        //item_count = item_list.len() as i64;
-                    }
-                    "misc" => {
-                        let query = items
-                            .filter(is_top.eq(false));
-                        //item_count = query.clone().count().get_result(conn)?;
-                        item_list = query
-                            .order(pub_at.desc())
-                            .limit(o.into())
-                            .offset((o * p_o).into())
-                            .load::<Item>(conn)?;
-                    }
-                    "newest" => {
-                        let query = items
-                            .filter(is_top.eq(false));  // need to filter? 
-                        //item_count = query.clone().count().get_result(conn)?;
-                        item_list = query
-                            .order(post_at.desc())
-                            .limit(o.into())
-                            .offset((o * p_o).into())
-                            .load::<Item>(conn)?;
-                    }
-                    _ => {
-                        let query = items
-                            .filter(is_top.eq(true))
-                            .filter(ty.eq(typ));
-                        //item_count = query.clone().count().get_result(conn)?;
-                        item_list = query
-                            .order(pub_at.desc())
-                            .limit(o.into())
-                            .offset((o * p_o).into())
-                            .load::<Item>(conn)?;
-                    }
-
-                }
+        let dup_list = items
+            .filter(dup_of.is_not_null())
+            .order(post_at.desc())
+            .limit(100)
+            .load::<Item>(conn)?;
+        let origin_ids: Vec<i32> = dup_list.iter().filter_map(|d| d.dup_of).collect();
+        let origin_list = items
+            .filter(id.eq(any(&origin_ids)))
+            .load::<Item>(conn)?;
+
+        let dups = dup_list
+            .into_iter()
+            .map(|d| {
+                let origin = origin_list
+                    .iter()
+                    .find(|o| Some(o.id) == d.dup_of)
+                    .cloned();
+                DupItem { item: d, origin }
+            })
+            .collect();
+        Ok(dups)
+    }
+}
+
+impl Message for QueryDups {
+    type Result = ServiceResult<Vec<DupItem>>;
+}
+
+// max bot items listed for review
+pub const PENDING_LIMIT: i64 = 200;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryPending;
+
+impl QueryPending {
+    fn get(
+        &self, 
+        conn: &PooledConn,
+    ) -> ServiceResult<Vec<Item>> {
+        use crate::schema::items::dsl::*;
+        let item_list = items
+            .filter(is_pending.eq(true))
+            .order(post_at.desc())
+            .limit(PENDING_LIMIT)
+            .load::<Item>(conn)?;
+        Ok(item_list)
+    }
+}
+
+impl Message for QueryPending {
+    type Result = ServiceResult<Vec<Item>>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ModerateItems {
+    pub action: String,         // approve|reject|edit
+    pub ids: Vec<i32>,
+    pub topic: Option<String>,  // to fix on approve or edit
+    pub ty: Option<String>,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "items"]
+struct PendingChange {
+    topic: Option<String>,
+    ty: Option<String>,
+    is_pending: Option<bool>,
+}
+
+impl ModerateItems {
+    // set topic and type if given, and approve
+    fn approve(
+        &self, 
+        conn: &PooledConn,
+    ) -> ServiceResult<usize> {
+        self.change(conn, Some(false))
+    }
+
+    // set topic or type, still pending
+    fn edit(
+        &self, 
+        conn: &PooledConn,
+    ) -> ServiceResult<usize> {
+        self.change(conn, None)
+    }
+
+    fn change(
+        &self, 
+        conn: &PooledConn,
+        pending: Option<bool>,
+    ) -> ServiceResult<usize> {
+        use crate::schema::items::dsl::{items, id, is_pending};
+        let ch = PendingChange {
+            topic: self.topic.clone(),
+            ty: self.ty.clone(),
+            is_pending: pending,
+        };
+        let changed = diesel::update(
+                items.filter(id.eq(any(&self.ids))).filter(is_pending.eq(true))
+            )
+            .set(&ch)
+            .get_results::<Item>(conn)?;
+
+        // show up in listings now
+        if pending == Some(false) {
+            for itm in changed.iter() {
+                del_listed_html(itm);
             }
-            QueryItems::Tt(t, typ, o, p) => {
-                let p_o = std::cmp::max(0, p-1);
-                match typ.to_lowercase().trim() {
-                    "misc" => {
-                        let query = items
-                            .filter(is_top.eq(false))
-                            .filter(topic.eq(t));
-                        //item_count = query.clone().count().get_result(conn)?;
-                        item_list = query
-                            .order(pub_at.desc())
-                            .limit(o.into())
-                            .offset((o * p_o).into())
-                            .load::<Item>(conn)?;
-                    }
-                    "newest" => {
-                        let query = items
-                            .filter(is_top.eq(false))  // need to filter? 
-                            .filter(topic.eq(t));
-                        //item_count = query.clone().count().get_result(conn)?;
-                        item_list = query
-                            .order(post_at.desc())
-                            .limit(o.into())
-                            .offset((o * p_o).into())
-                            .load::<Item>(conn)?;
-                    }
-                    _ =>  {
-                        let query = items
-                            .filter(is_top.eq(true))
-                            .filter(topic.eq(t))
-                            .filter(ty.eq(typ));
-                        //item_count = query.clone().count().get_result(conn)?;
-                        item_list = query
-                            .order(pub_at.desc())
-                            .limit(o.into())
-                            .offset((o * p_o).into())
-                            .load::<Item>(conn)?;
-                    }
-                }
+        }
+        Ok(changed.len())
+    }
+
+    // del, and never crawl the links again
+    fn reject(
+        &self, 
+        conn: &PooledConn,
+    ) -> ServiceResult<usize> {
+        use crate::schema::items::dsl::{items, id, is_pending};
+        use crate::bot::seen::reject_urls;
+        let n = conn.transaction::<usize, diesel::result::Error, _>(|| {
+            let rejected = diesel::delete(
+                    items.filter(id.eq(any(&self.ids))).filter(is_pending.eq(true))
+                )
+                .get_results::<Item>(conn)?;
+            let links: Vec<String> = rejected
+                .iter()
+                .map(|i| i.canonical_link.clone())
+                .collect();
+            reject_urls(conn, &links)?;
+            Ok(rejected.len())
+        })?;
+        Ok(n)
+    }
+
+    fn validate(&self) -> ServiceResult<()> {
+        use crate::view::{TOPIC_VEC, TY_VEC};
+        let act = self.action.to_uppercase();
+        let topic_ok = self.topic.as_ref()
+            .map(|t| t != "all" && TOPIC_VEC.contains(&t.as_str()))
+            .unwrap_or(true);
+        let ty_ok = self.ty.as_ref()
+            .map(|t| TY_VEC.contains(&t.as_str()))
+            .unwrap_or(true);
+        let edit_ok = act != "EDIT" || self.topic.is_some() || self.ty.is_some();
+        let check = (act == "APPROVE" || act == "REJECT" || act == "EDIT")
+            && self.ids.len() > 0
+            && self.ids.len() as i64 <= PENDING_LIMIT
+            && topic_ok && ty_ok && edit_ok;
+
+        if check {
+            Ok(())
+        } else {
+            error!("moderate items");
+            Err(ServiceError::BadRequest("Invalid Action or Items".into()))
+        }
+    }
+}
+
+impl Message for ModerateItems {
+    type Result = ServiceResult<usize>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ClickItem {
+    pub id: i32,
+    pub uname: String,
+}
+
+impl Message for ClickItem {
+    type Result = ServiceResult<i32>;
+}
+
+// max items per page
+pub const MAX_PERPAGE: i32 = 100;
+
+// listings order, newest first, the id breaks ties
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum ItemOrder {
+    PubAt,
+    PostAt,
+    Hot,     // vote and clicks decayed by age
+}
+
+// hot, HN-style: (vote - 1 + clicks * click_weight) / (age_hours + 2) ^ gravity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HotRank {
+    pub gravity: f64,       // the higher, the faster old items sink
+    pub click_weight: f64,  // a click worth of a vote, 0 to ignore clicks
+}
+
+impl HotRank {
+    // env: HOT_GRAVITY, HOT_CLICK_WEIGHT
+    pub fn from_env() -> Self {
+        let var = |k: &str, default: f64| dotenv::var(k)
+            .ok()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+            .unwrap_or(default);
+        HotRank {
+            gravity: var("HOT_GRAVITY", 1.8),
+            click_weight: var("HOT_CLICK_WEIGHT", 0.1),
+        }
+    }
+
+    // the score as sql, age in hours since pub_at till rank_at, future as 0;
+    // all numbers formatted by us, nothing from the request as is
+    pub fn sql(&self, rank_at: &NaiveDateTime) -> String {
+        format!(
+            "(vote - 1 + clicks * {:?}) / power(greatest(extract(epoch from \
+            ('{}'::timestamp - pub_at::timestamp))::float8 / 3600, 0) + 2, {:?})",
+            self.click_weight,
+            rank_at.format("%Y-%m-%d %H:%M:%S%.6f"),
+            self.gravity,
+        )
+    }
+}
+
+// the position in a listing, as ?after= or ?before= in url
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ItemCursor {
+    pub at: NaiveDateTime,  // pub_at or post_at of the item
+    pub id: i32,
+}
+
+impl ItemCursor {
+    pub fn of(itm: &Item, order: ItemOrder) -> Self {
+        let at = match order {
+            ItemOrder::PubAt => itm.pub_at.and_hms(0, 0, 0),
+            ItemOrder::PostAt | ItemOrder::Hot => itm.post_at,
+        };
+        ItemCursor { at, id: itm.id }
+    }
+
+    // micros_id, in microseconds as post_at in db, not skip or repeat the
+    // items posted in the same second
+    pub fn encode(&self) -> String {
+        format!("{}_{}", to_micros(&self.at), self.id)
+    }
+
+    pub fn decode(s: &str) -> Option<Self> {
+        let mut parts = s.trim().splitn(2, '_');
+        let at = from_micros(parts.next()?.parse::<i64>().ok()?)?;
+        let id = parts.next()?.parse::<i32>().ok()?;
+        Some(ItemCursor { at, id })
+    }
+}
+
+// the position in the hot listing: the time ranked at and the offset, as
+// the order keeps the same across the pages only at the same time
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct HotCursor {
+    pub at: NaiveDateTime,
+    pub offset: i64,
+}
+
+impl HotCursor {
+    // hot_micros_offset
+    pub fn encode(&self) -> String {
+        format!("hot_{}_{}", to_micros(&self.at), self.offset)
+    }
+
+    pub fn decode(s: &str) -> Option<Self> {
+        let mut parts = s.trim().splitn(3, '_');
+        if parts.next()? != "hot" {
+            return None;
+        }
+        let at = from_micros(parts.next()?.parse::<i64>().ok()?)?;
+        let offset = parts.next()?.parse::<i64>().ok()?;
+        if offset < 0 {
+            return None;
+        }
+        Some(HotCursor { at, offset })
+    }
+}
+
+fn to_micros(at: &NaiveDateTime) -> i64 {
+    at.timestamp() * 1_000_000 + at.timestamp_subsec_micros() as i64
+}
+
+fn from_micros(micros: i64) -> Option<NaiveDateTime> {
+    let secs = micros.div_euclid(1_000_000);
+    let nsecs = micros.rem_euclid(1_000_000) as u32 * 1000;
+    NaiveDateTime::from_timestamp_opt(secs, nsecs)
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct PageArgs {
+    pub perpage: i32,
+    pub page: i32,                   // by offset if no cursor, from 1
+    pub after: Option<ItemCursor>,   // the next page
+    pub before: Option<ItemCursor>,  // the prev page
+    pub hot_after: Option<HotCursor>,  // the same, of the hot listing
+    pub hot_before: Option<HotCursor>,
+}
+
+impl PageArgs {
+    pub fn new(perpage: i32, page: i32) -> Self {
+        PageArgs { perpage, page, ..Default::default() }
+    }
+
+    // the cursor goes first, bad one ignored
+    pub fn cursor(mut self, after: Option<&str>, before: Option<&str>) -> Self {
+        self.after = after.and_then(ItemCursor::decode);
+        self.before = before.and_then(ItemCursor::decode);
+        self.hot_after = after.and_then(HotCursor::decode);
+        self.hot_before = before.and_then(HotCursor::decode);
+        self
+    }
+
+    fn limit(&self) -> i64 {
+        std::cmp::min(std::cmp::max(self.perpage, 1), MAX_PERPAGE) as i64
+    }
+
+    fn offset(&self) -> i64 {
+        if self.after.is_some() || self.before.is_some()
+            || self.hot_after.is_some() || self.hot_before.is_some()
+        {
+            0
+        } else {
+            std::cmp::max(self.page - 1, 0) as i64 * self.limit()
+        }
+    }
+}
+
+type BoxedItems = items::BoxedQuery<'static, diesel::pg::Pg>;
+
+// keyset on (key, id): after, desc; before, asc then reversed
+fn keyset(
+    query: BoxedItems, 
+    order: ItemOrder, 
+    pa: &PageArgs,
+) -> BoxedItems {
+    use crate::schema::items::dsl::*;
+    match (order, pa.after.as_ref(), pa.before.as_ref()) {
+        (ItemOrder::PubAt, Some(c), _) => {
+            let d = c.at.date();
+            query
+                .filter(pub_at.lt(d).or(pub_at.eq(d).and(id.lt(c.id))))
+                .order((pub_at.desc(), id.desc()))
+        }
+        (ItemOrder::PubAt, None, Some(c)) => {
+            let d = c.at.date();
+            query
+                .filter(pub_at.gt(d).or(pub_at.eq(d).and(id.gt(c.id))))
+                .order((pub_at.asc(), id.asc()))
+        }
+        (ItemOrder::PubAt, None, None) => {
+            query.order((pub_at.desc(), id.desc()))
+        }
+        (ItemOrder::PostAt, Some(c), _) => {
+            query
+                .filter(post_at.lt(c.at).or(post_at.eq(c.at).and(id.lt(c.id))))
+                .order((post_at.desc(), id.desc()))
+        }
+        (ItemOrder::PostAt, None, Some(c)) => {
+            query
+                .filter(post_at.gt(c.at).or(post_at.eq(c.at).and(id.gt(c.id))))
+                .order((post_at.asc(), id.asc()))
+        }
+        (ItemOrder::PostAt, None, None) => {
+            query.order((post_at.desc(), id.desc()))
+        }
+        (ItemOrder::Hot, _, _) => query,  // ranked in load_hot
+    }
+}
+
+// hot changes w/ time, paged by offset ranked at the time in HotCursor
+fn load_hot(
+    query: BoxedItems, 
+    count: i64,
+    pa: &PageArgs, 
+    conn: &PooledConn,
+) -> ServiceResult<ItemsMsg> {
+    use crate::schema::items::dsl::id;
+    use diesel::dsl::sql;
+    use diesel::sql_types::Double;
+
+    let n = pa.limit();
+    let (rank_at, offset) = match (pa.hot_after.as_ref(), pa.hot_before.as_ref()) {
+        (Some(c), _) => (c.at, c.offset),
+        (None, Some(c)) => (c.at, std::cmp::max(c.offset - n, 0)),
+        _ => (Utc::now().naive_utc(), pa.offset()),
+    };
+
+    let score = sql::<Double>(&HotRank::from_env().sql(&rank_at));
+    let mut item_list = query
+        .order((score.desc(), id.desc()))
+        .limit(n + 1)
+        .offset(offset)
+        .load::<Item>(conn)?;
+    let has_more = item_list.len() as i64 > n;
+    item_list.truncate(n as usize);
+
+    let cursor = |o: i64| HotCursor { at: rank_at, offset: o }.encode();
+    Ok(ItemsMsg {
+        items: item_list,
+        count,
+        next: if has_more { Some(cursor(offset + n)) } else { None },
+        prev: if offset > 0 { Some(cursor(offset)) } else { None },
+        ..Default::default()
+    })
+}
+
+// a page of the listing w/ the total and the cursors around
+fn load_page<F>(
+    filtered: F, 
+    order: ItemOrder, 
+    pa: &PageArgs, 
+    conn: &PooledConn,
+) -> ServiceResult<ItemsMsg> 
+where
+    F: Fn() -> BoxedItems,
+{
+    let count: i64 = filtered().count().get_result(conn)?;
+    if order == ItemOrder::Hot {
+        return load_hot(filtered(), count, pa, conn);
+    }
+
+    let n = pa.limit();
+    let mut item_list = keyset(filtered(), order, pa)
+        .limit(n + 1)
+        .offset(pa.offset())
+        .load::<Item>(conn)?;
+    let has_more = item_list.len() as i64 > n;
+    item_list.truncate(n as usize);
+
+    let backward = pa.after.is_none() && pa.before.is_some();
+    if backward {
+        item_list.reverse();
+    }
+    let first = item_list.first().map(|i| ItemCursor::of(i, order).encode());
+    let last = item_list.last().map(|i| ItemCursor::of(i, order).encode());
+    let (next, prev) = if backward {
+        (last, if has_more { first } else { None })
+    } else {
+        let not_first = pa.after.is_some() || pa.page > 1;
+        (if has_more { last } else { None }, if not_first { first } else { None })
+    };
+
+    Ok(ItemsMsg {
+        items: item_list,
+        count,
+        next,
+        prev,
+        ..Default::default()
+    })
+}
+
+// filters of listing, all optional, and together
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ItemFilter {
+    pub topic: Option<String>,     // all if none
+    pub ty: Option<String>,
+    pub author: Option<String>,
+    pub host: Option<String>,      // link_host
+    pub post_by: Option<String>,
+    pub voted_by: Option<String>,  // items voted by the user
+    pub since: Option<NaiveDate>,  // pub_at, inclusive
+    pub until: Option<NaiveDate>,
+    pub top: Option<bool>,
+    pub sort: Option<String>,      // pub|post, newest first; hot
+}
+
+// trimmed, none if empty
+fn non_empty(s: &Option<String>) -> Option<&str> {
+    s.as_deref().map(|v| v.trim()).filter(|v| v.len() > 0)
+}
+
+impl ItemFilter {
+    // the collection of topic-ty,
+    // special topic: all, from(ty as author); special ty: index, Misc, newest, hot
+    pub fn of_collection(tpc: &str, typ: &str) -> Self {
+        let mut f = ItemFilter::default();
+        match tpc.trim().to_lowercase().as_str() {
+            "from" => {
+                f.author = Some(typ.to_owned());
+                return f;
             }
-            QueryItems::Topic(t, o, p) => {
-                let query = items
-                    .filter(is_top.eq(true))
-                    .filter(topic.eq(t));
-                let p_o = std::cmp::max(0, p-1);
-                //item_count = query.clone().count().get_result(conn)?;
-                item_list = query
-                    .order(pub_at.desc())
-                    .limit(o.into())
-                    .offset((o * p_o).into())
-                    .load::<Item>(conn)?;
+            "all" => {}
+            _ => f.topic = Some(tpc.trim().to_owned()),
+        }
+        match typ.to_lowercase().trim() {
+            "index" => {
+                f.top = Some(true);
+                f.sort = Some("post".to_owned());
             }
-            QueryItems::Ty(t, o, p) => {
-                let query = items
-                    .filter(is_top.eq(true))
-                    .filter(ty.eq(t));
-                let p_o = std::cmp::max(0, p-1);
-                //item_count = query.clone().count().get_result(conn)?;
-                item_list = query
-                    .order(pub_at.desc())
-                    .limit(o.into())
-                    .offset((o * p_o).into())
-                    .load::<Item>(conn)?;
+            "misc" => {
+                f.top = Some(false);
             }
-            QueryItems::User(u, a, o, p) => {
-                let action = a.trim();
-                let p_o = std::cmp::max(0, p-1);
-
-                match action {
-                    "submit" => {
-                        let query = items.filter(post_by.eq(u));
-                        item_count = query.clone().count().get_result(conn)?;
-                        item_list = query
-                            .order(pub_at.desc())
-                            .limit(o.into())
-                            .offset((o * p_o).into())
-                            .load::<Item>(conn)?;
-                    }
-                    "vote" => {
-                        use crate::schema::voteitems::dsl::*;
-                        let itemid_list = voteitems
-                            .filter(uname.eq(u))
-                            .filter(vote_as.eq(1))
-                            .select(item_id)
-                            .load::<i32>(conn)?;
-                        item_count = itemid_list.len() as i64;
-                        item_list = items
-                            .filter(id.eq(any(&itemid_list)))
-                            .order(pub_at.desc())
-                            .limit(o.into())
-                            .offset((o * p_o).into())
-                            .load::<Item>(conn)?;
-                    }
-                    _ => {}
-                }
+            "newest" => {
+                f.top = Some(false);  // need to filter? 
+                f.sort = Some("post".to_owned());
             }
-            QueryItems::Author(a, o, p) => {
-                let query = items
-                    //.filter(is_top.eq(true))
-                    .filter(author.eq(a));
-                let p_o = std::cmp::max(0, p-1);
-                //item_count = query.clone().count().get_result(conn)?;
-                item_list = query
-                    .order(pub_at.desc())
-                    .limit(o.into())
-                    .offset((o * p_o).into())
-                    .load::<Item>(conn)?;
+            "hot" => {
+                f.sort = Some("hot".to_owned());
             }
             _ => {
-                item_list = items
-                    .filter(is_top.eq(true))
-                    .order(pub_at.desc())
-                    .limit(42)
-                    .load::<Item>(conn)?;
-                //item_count = item_list.len() as i64;
+                f.top = Some(true);
+                f.ty = Some(typ.to_owned());
             }
         }
-        Ok((item_list, item_count))
+        f
+    }
+
+    pub fn order(&self) -> ItemOrder {
+        match non_empty(&self.sort) {
+            Some("post") => ItemOrder::PostAt,
+            Some("hot") => ItemOrder::Hot,
+            _ => ItemOrder::PubAt,
+        }
+    }
+
+    // of the user: submitted or voted, all as they are
+    fn is_personal(&self) -> bool {
+        non_empty(&self.post_by).is_some() || non_empty(&self.voted_by).is_some()
+    }
+
+    // the listed: not dup, not pending, unless personal
+    fn query(&self) -> BoxedItems {
+        use crate::schema::items::dsl::*;
+        let mut query = items.into_boxed();
+        if !self.is_personal() {
+            query = query
+                .filter(dup_of.is_null())
+                .filter(is_pending.eq(false));
+        }
+        if let Some(t) = non_empty(&self.topic).filter(|t| *t != "all") {
+            query = query.filter(topic.eq(t.to_owned()));
+        }
+        if let Some(t) = non_empty(&self.ty) {
+            query = query.filter(ty.eq(t.to_owned()));
+        }
+        if let Some(a) = non_empty(&self.author) {
+            query = query.filter(author.eq(a.to_owned()));
+        }
+        if let Some(h) = non_empty(&self.host) {
+            query = query.filter(link_host.eq(h.to_owned()));
+        }
+        if let Some(u) = non_empty(&self.post_by) {
+            query = query.filter(post_by.eq(u.to_owned()));
+        }
+        if let Some(u) = non_empty(&self.voted_by) {
+            use crate::schema::voteitems::dsl as v;
+            let voted = v::voteitems
+                .filter(v::uname.eq(u.to_owned()))
+                .filter(v::vote_as.eq(1))
+                .select(v::item_id);
+            query = query.filter(id.eq_any(voted));
+        }
+        if let Some(d) = self.since {
+            query = query.filter(pub_at.ge(d));
+        }
+        if let Some(d) = self.until {
+            query = query.filter(pub_at.le(d));
+        }
+        if let Some(t) = self.top {
+            query = query.filter(is_top.eq(t));
+        }
+        query
+    }
+
+    pub fn validate(&self) -> ServiceResult<()> {
+        use crate::view::{TOPIC_VEC, TY_VEC};
+        let check = non_empty(&self.topic)
+                .map(|t| TOPIC_VEC.contains(&t)).unwrap_or(true)
+            && non_empty(&self.ty)
+                .map(|t| TY_VEC.contains(&t)).unwrap_or(true)
+            && non_empty(&self.sort)
+                .map(|s| s == "pub" || s == "post" || s == "hot").unwrap_or(true)
+            && match (self.since, self.until) {
+                (Some(s), Some(u)) => s <= u,
+                _ => true,
+            };
+
+        if check {
+            Ok(())
+        } else {
+            Err(ServiceError::BadRequest("Invalid Filter".into()))
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryItems {
+    pub filter: ItemFilter,
+    pub page: PageArgs,
+    pub uname: Option<String>,  // the caller, for my_votes
+}
+
+impl QueryItems {
+    pub fn get(
+        self, 
+        conn: &PooledConn,
+    ) -> ServiceResult<ItemsMsg> {
+        let f = &self.filter;
+        let mut msg = load_page(|| f.query(), f.order(), &self.page, conn)?;
+        if let Some(u) = self.uname.as_ref() {
+            let ids: Vec<i32> = msg.items.iter().map(|i| i.id).collect();
+            msg.my_votes = my_votes(conn, u, &ids)?;
+        }
+        Ok(msg)
     }
 }
 
 impl Message for QueryItems {
-    type Result = ServiceResult<(Vec<Item>, i64)>;
+    type Result = ServiceResult<ItemsMsg>;
 }
 
 #[derive(
@@ -881,31 +1886,149 @@ pub struct VoteItem {
 }
 
 impl VoteItem {
-    fn new(
+    // new, or switch
+    fn save(
         &self, 
-        conn: &PooledConn,
-    ) -> ServiceResult<usize> {
-        use crate::schema::voteitems::dsl::{voteitems};
-        let vote_count = diesel::insert_into(voteitems)
+        conn: &PgConnection,
+    ) -> QueryResult<usize> {
+        use crate::schema::voteitems::dsl::{voteitems, uname, item_id};
+        diesel::insert_into(voteitems)
             .values(self)
-            .on_conflict_do_nothing()
-            .execute(conn)?;
-
-        Ok(vote_count)
+            .on_conflict((uname, item_id))
+            .do_update()
+            .set(self)
+            .execute(conn)
     }
 
     fn del(
         &self, 
-        conn: &PooledConn,
-    ) -> ServiceResult<usize> {
+        conn: &PgConnection,
+    ) -> QueryResult<usize> {
         use crate::schema::voteitems::dsl::{voteitems, uname, item_id};
-
-        let unvote = diesel::delete(
+        diesel::delete(
             voteitems
                 .filter(uname.eq(&self.uname))
                 .filter(item_id.eq(&self.item_id))
-            ).execute(conn)?;
+            ).execute(conn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_and_page() {
+        let c = ItemCursor {
+            at: NaiveDate::from_ymd(2020, 11, 19).and_hms(8, 30, 0),
+            id: 42,
+        };
+        assert_eq!(c.encode(), "1605774600000000_42");
+        assert_eq!(ItemCursor::decode(&c.encode()), Some(c));
+        assert_eq!(ItemCursor::decode("1605774600000000"), None);
+        assert_eq!(ItemCursor::decode("x_42"), None);
+        // sub-second, as post_at
+        let c = ItemCursor {
+            at: NaiveDate::from_ymd(2020, 11, 19).and_hms_micro(8, 30, 0, 123_456),
+            id: 42,
+        };
+        assert_eq!(c.encode(), "1605774600123456_42");
+        assert_eq!(ItemCursor::decode(&c.encode()), Some(c));
+
+        let pa = PageArgs::new(42, 3);
+        assert_eq!((pa.limit(), pa.offset()), (42, 84));
+        let pa = PageArgs::new(1000, 3).cursor(Some("1605774600000000_42"), None);
+        assert_eq!((pa.limit(), pa.offset()), (MAX_PERPAGE as i64, 0));
+        let pa = PageArgs::new(0, 0).cursor(Some("bad"), None);
+        assert_eq!((pa.limit(), pa.offset()), (1, 0));
+        assert!(pa.after.is_none());
+
+        let h = HotCursor {
+            at: NaiveDate::from_ymd(2020, 11, 19).and_hms_micro(8, 30, 0, 123_456),
+            offset: 84,
+        };
+        assert_eq!(h.encode(), "hot_1605774600123456_84");
+        assert_eq!(HotCursor::decode(&h.encode()), Some(h));
+        assert_eq!(HotCursor::decode("1605774600123456_84"), None);
+        assert_eq!(HotCursor::decode("hot_1605774600123456_-42"), None);
+        // each cursor reads its own only
+        assert_eq!(ItemCursor::decode("hot_1605774600123456_84"), None);
+        let pa = PageArgs::new(42, 3).cursor(Some("hot_1605774600123456_84"), None);
+        assert!(pa.after.is_none() && pa.hot_after.is_some());
+        assert_eq!(pa.offset(), 0);
+    }
+
+    #[test]
+    fn vote_switch_and_clear() {
+        assert_eq!(VoteAct::parse(" vote "), Some(VoteAct::Vote));
+        assert_eq!(VoteAct::parse("Clear"), Some(VoteAct::Clear));
+        assert_eq!(VoteAct::parse("up"), None);
+
+        // new, again, switch, clear
+        assert_eq!(VoteAct::Vote.apply(0), (1, 1));
+        assert_eq!(VoteAct::Vote.apply(1), (1, 0));
+        assert_eq!(VoteAct::Veto.apply(1), (-1, -2));
+        assert_eq!(VoteAct::Vote.apply(-1), (1, 2));
+        assert_eq!(VoteAct::Clear.apply(-1), (0, 1));
+        assert_eq!(VoteAct::Clear.apply(1), (0, -1));
+        assert_eq!(VoteAct::Clear.apply(0), (0, 0));
+    }
+
+    #[test]
+    fn hot_rank() {
+        let hot = HotRank { gravity: 1.8, click_weight: 0.1 };
+        let at = NaiveDate::from_ymd(2020, 11, 19).and_hms_micro(8, 30, 0, 42);
+        assert_eq!(
+            hot.sql(&at),
+            "(vote - 1 + clicks * 0.1) / power(greatest(extract(epoch from \
+            ('2020-11-19 08:30:00.000042'::timestamp - pub_at::timestamp))::float8 \
+            / 3600, 0) + 2, 1.8)",
+        );
+        let no_click = HotRank { click_weight: 0.0, gravity: 2.0 };
+        assert!(no_click.sql(&at).starts_with("(vote - 1 + clicks * 0.0) / "));
+        assert!(no_click.sql(&at).ends_with(" + 2, 2.0)"));
+
+        let f = ItemFilter::of_collection("Go", "hot");
+        assert_eq!((f.top, f.order()), (None, ItemOrder::Hot));
+        assert!(f.validate().is_ok());
+    }
 
-        Ok(unvote)
+    #[test]
+    fn filter_of_collection() {
+        let f = ItemFilter::of_collection("all", "index");
+        assert_eq!((f.topic.as_ref(), f.top), (None, Some(true)));
+        assert_eq!(f.order(), ItemOrder::PostAt);
+
+        let f = ItemFilter::of_collection("Rust", "Book");
+        assert_eq!(f.topic.as_deref(), Some("Rust"));
+        assert_eq!(f.ty.as_deref(), Some("Book"));
+        assert_eq!(f.order(), ItemOrder::PubAt);
+        assert!(f.validate().is_ok());
+
+        let f = ItemFilter::of_collection("from", "Jane Doe");
+        assert_eq!(f.author.as_deref(), Some("Jane Doe"));
+        assert_eq!((f.topic.as_ref(), f.top), (None, None));
+
+        let mut f = ItemFilter::default();
+        f.ty = Some("Book".to_owned());
+        f.since = Some(NaiveDate::from_ymd(2020, 1, 1));
+        f.until = Some(NaiveDate::from_ymd(2020, 12, 31));
+        assert!(f.validate().is_ok());
+        f.sort = Some("new".to_owned());
+        assert!(f.validate().is_err());
+        f.sort = None;
+        f.until = Some(NaiveDate::from_ymd(2019, 12, 31));
+        assert!(f.validate().is_err());
+
+        // the listed only in collections, all in the lists of user
+        use diesel::{debug_query, pg::Pg};
+        let f = ItemFilter::of_collection("Rust", "Book");
+        assert!(debug_query::<Pg, _>(&f.query()).to_string().contains("dup_of"));
+        let mut f = ItemFilter::default();
+        f.post_by = Some("toplog".to_owned());
+        assert!(f.is_personal());
+        assert!(!debug_query::<Pg, _>(&f.query()).to_string().contains("dup_of"));
+        f.post_by = Some(" ".to_owned());
+        assert!(!f.is_personal());
     }
 }
@@ -1,7 +1,13 @@
 
 pub mod auth;
 pub mod blog;
+pub mod bookmark;
+pub mod classify;
+pub mod crawl;
 pub mod item;
+pub mod revision;
+pub mod search;
+pub mod source;
 
 #[derive(Deserialize, Clone)]
 pub struct ReqQuery {
@@ -9,6 +15,16 @@ pub struct ReqQuery {
     kw: String,
     page: i32,
     perpage: i32,
+    after: Option<String>,   // cursor, over page
+    before: Option<String>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PageQuery {
+    perpage: Option<i32>,
+    page: Option<i32>,
+    after: Option<String>,   // cursor, over page
+    before: Option<String>,
 }
 
 #[derive(Deserialize, Clone)]
@@ -18,6 +34,7 @@ pub struct ActionQuery {
 
 use crate::api::auth::CheckUser;
 use crate::api::item::Item;
+use std::collections::HashMap;
 
 // general response msg struct
 #[derive(Deserialize, Serialize, Debug)]
@@ -37,10 +54,14 @@ pub struct AuthMsg {
     pub omg: bool, // if it is the admin
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Default)]
 pub struct ItemsMsg {
     pub items: Vec<Item>,
     pub count: i64,
+    pub next: Option<String>,  // cursor of the next page, none if the last
+    pub prev: Option<String>,
+    #[serde(default)]
+    pub my_votes: HashMap<i32, i16>,  // of the caller, by item id, voted only
 }
 
 // msg for get user info
@@ -0,0 +1,322 @@
+// api.revision: the edit history of items, and revert
+
+use actix::{Handler, Message};
+use actix_web::{
+    web::{Data, Path},
+    Error, HttpResponse, ResponseError,
+    Result,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+use log::error;
+
+use crate::errors::{ServiceError, ServiceResult};
+use crate::api::auth::CheckCan;
+use crate::api::item::{Item, UpdateItem};
+use crate::{Dba, DbAddr, PooledConn};
+use crate::schema::{item_revisions};
+
+// GET: /api/items/{id}/revisions
+//
+// newest first
+pub async fn get_list(
+    p: Path<i32>,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let res = db.send(QueryRevisions{ item_id: p.into_inner() }).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<QueryRevisions> for Dba {
+    type Result = ServiceResult<Vec<ItemRevision>>;
+
+    fn handle(&mut self, q: QueryRevisions, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        q.get(conn)
+    }
+}
+
+// PUT: /api/items/{id}/revisions/{rid}
+//
+// undo the revision and all after it, recorded as a new revision
+pub async fn revert(
+    p: Path<(i32, i32)>,
+    can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let (item_id, rev_id) = p.into_inner();
+    let rv = RevertItem {
+        item_id,
+        rev_id,
+        edit_by: can.uname,
+    };
+    let res = db.send(rv).await?;
+    match res {
+        Ok(i) => Ok(HttpResponse::Ok().json(i)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<RevertItem> for Dba {
+    type Result = ServiceResult<Item>;
+
+    fn handle(&mut self, rv: RevertItem, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        rv.revert(conn)
+    }
+}
+
+// =================================================================================
+// =================================================================================
+// Model
+// =================================================================================
+
+// a field changed in an edit
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Identifiable, Queryable)]
+#[table_name = "item_revisions"]
+pub struct ItemRevision {
+    pub id: i32,
+    pub item_id: i32,
+    pub edit_by: String,
+    pub edit_at: NaiveDateTime,
+    pub diff: serde_json::Value,  // [FieldDiff]
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "item_revisions"]
+struct NewItemRevision<'a> {
+    item_id: i32,
+    edit_by: &'a str,
+    diff: serde_json::Value,
+}
+
+impl ItemRevision {
+    pub fn changes(&self) -> Vec<FieldDiff> {
+        serde_json::from_value(self.diff.clone()).unwrap_or_default()
+    }
+
+    // record the fields changed from old to new, if any
+    pub fn record(
+        conn: &PgConnection,
+        old: &Item,
+        new: &Item,
+        edit_by: &str,
+    ) -> QueryResult<usize> {
+        let changes = diff_items(old, new);
+        if changes.len() == 0 {
+            return Ok(0);
+        }
+        let rev = NewItemRevision {
+            item_id: new.id,
+            edit_by,
+            diff: serde_json::to_value(&changes).unwrap_or_default(),
+        };
+        diesel::insert_into(item_revisions::table)
+            .values(&rev)
+            .execute(conn)
+    }
+}
+
+const DATE_FMT: &str = "%Y-%m-%d";
+
+// the fields editable via UpdateItem
+pub fn diff_items(old: &Item, new: &Item) -> Vec<FieldDiff> {
+    let pairs = vec![
+        ("title", old.title.clone(), new.title.clone()),
+        ("content", old.content.clone(), new.content.clone()),
+        ("logo", old.logo.clone(), new.logo.clone()),
+        ("author", old.author.clone(), new.author.clone()),
+        ("ty", old.ty.clone(), new.ty.clone()),
+        ("topic", old.topic.clone(), new.topic.clone()),
+        ("link", old.link.clone(), new.link.clone()),
+        (
+            "pub_at",
+            old.pub_at.format(DATE_FMT).to_string(),
+            new.pub_at.format(DATE_FMT).to_string(),
+        ),
+    ];
+    pairs.into_iter()
+        .filter(|(_, o, n)| o != n)
+        .map(|(f, o, n)| FieldDiff { field: f.to_owned(), old: o, new: n })
+        .collect()
+}
+
+// the item before the revisions, undo newest first
+pub fn undo_revisions(item: &Item, revs: &[ItemRevision]) -> UpdateItem {
+    let mut up = UpdateItem {
+        id: item.id,
+        title: item.title.clone(),
+        content: item.content.clone(),
+        logo: item.logo.clone(),
+        author: item.author.clone(),
+        ty: item.ty.clone(),
+        topic: item.topic.clone(),
+        link: item.link.clone(),
+        post_by: item.post_by.clone(),
+        pub_at: item.pub_at,
+    };
+    let mut revs: Vec<&ItemRevision> = revs.iter().collect();
+    revs.sort_by(|a, b| b.id.cmp(&a.id));
+    for rev in revs {
+        for c in rev.changes() {
+            match c.field.as_str() {
+                "title" => up.title = c.old,
+                "content" => up.content = c.old,
+                "logo" => up.logo = c.old,
+                "author" => up.author = c.old,
+                "ty" => up.ty = c.old,
+                "topic" => up.topic = c.old,
+                "link" => up.link = c.old,
+                "pub_at" => {
+                    if let Ok(d) = NaiveDate::parse_from_str(&c.old, DATE_FMT) {
+                        up.pub_at = d;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    up
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QueryRevisions {
+    pub item_id: i32,
+}
+
+impl QueryRevisions {
+    pub fn get(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Vec<ItemRevision>> {
+        use crate::schema::item_revisions::dsl::*;
+        let rev_list = item_revisions
+            .filter(item_id.eq(self.item_id))
+            .order(id.desc())
+            .load::<ItemRevision>(conn)?;
+        Ok(rev_list)
+    }
+}
+
+impl Message for QueryRevisions {
+    type Result = ServiceResult<Vec<ItemRevision>>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RevertItem {
+    pub item_id: i32,
+    pub rev_id: i32,
+    pub edit_by: String,
+}
+
+impl RevertItem {
+    fn revert(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Item> {
+        use crate::schema::items::dsl::items;
+        use crate::schema::item_revisions::dsl::{item_revisions, item_id, id};
+        let item = items.find(self.item_id).get_result::<Item>(conn)?;
+        let revs = item_revisions
+            .filter(item_id.eq(self.item_id))
+            .filter(id.ge(self.rev_id))
+            .load::<ItemRevision>(conn)?;
+        if !revs.iter().any(|r| r.id == self.rev_id) {
+            return Err(ServiceError::NotFound("No Such Revision".into()));
+        }
+
+        // as an edit by the admin
+        let mut up = undo_revisions(&item, &revs);
+        up.post_by = self.edit_by.clone();
+        up.update(conn)
+    }
+}
+
+impl Message for RevertItem {
+    type Result = ServiceResult<Item>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> Item {
+        Item {
+            id: 1,
+            title: "Announcing Rust 1.48".to_owned(),
+            content: "".to_owned(),
+            logo: "".to_owned(),
+            author: "The Rust Release Team".to_owned(),
+            ty: "Article".to_owned(),
+            topic: "Rust".to_owned(),
+            link: "https://blog.rust-lang.org/2020/11/19/Rust-1.48.html".to_owned(),
+            link_host: "blog.rust-lang.org".to_owned(),
+            pub_at: NaiveDate::from_ymd(2020, 11, 19),
+            post_by: "bot".to_owned(),
+            post_at: NaiveDate::from_ymd(2020, 11, 19).and_hms(8, 0, 0),
+            is_top: false,
+            vote: 0,
+            simhash: 0,
+            dup_of: None,
+            is_dead: false,
+            is_pending: false,
+            clicks: 0,
+            canonical_link: "https://blog.rust-lang.org/2020/11/19/Rust-1.48.html".to_owned(),
+        }
+    }
+
+    fn rev(rid: i32, old: &Item, new: &Item) -> ItemRevision {
+        ItemRevision {
+            id: rid,
+            item_id: new.id,
+            edit_by: "admin".to_owned(),
+            edit_at: new.post_at,
+            diff: serde_json::to_value(diff_items(old, new)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn diff_and_undo() {
+        let v0 = item();
+        let mut v1 = v0.clone();
+        v1.title = "Rust 1.48".to_owned();
+        v1.pub_at = NaiveDate::from_ymd(2020, 11, 20);
+        let mut v2 = v1.clone();
+        v2.topic = "Go".to_owned();
+
+        let d = diff_items(&v0, &v1);
+        assert_eq!(d.len(), 2);
+        assert_eq!(d[0], FieldDiff {
+            field: "title".to_owned(),
+            old: "Announcing Rust 1.48".to_owned(),
+            new: "Rust 1.48".to_owned(),
+        });
+        assert_eq!(d[1].old, "2020-11-19");
+        assert!(diff_items(&v2, &v2).is_empty());
+
+        let r1 = rev(1, &v0, &v1);
+        let r2 = rev(2, &v1, &v2);
+
+        // before the last one
+        let up = undo_revisions(&v2, &[r2.clone()]);
+        assert_eq!(up.title, v1.title);
+        assert_eq!(up.topic, "Rust");
+
+        // back to the origin, any order
+        let up = undo_revisions(&v2, &[r1, r2]);
+        assert_eq!(up.title, v0.title);
+        assert_eq!(up.topic, v0.topic);
+        assert_eq!(up.pub_at, v0.pub_at);
+    }
+}
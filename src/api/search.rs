@@ -0,0 +1,220 @@
+// api.search: full-text search over items and blogs
+
+use actix::{Handler, Message};
+use actix_web::{
+    web::{Data, Query},
+    Error, HttpResponse, ResponseError,
+    Result,
+};
+use diesel::prelude::*;
+use diesel::dsl::sql;
+use diesel::sql_types::{Bool, Float, Text};
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+use log::error;
+use std::collections::HashMap;
+
+use crate::errors::{ServiceError, ServiceResult};
+use crate::api::{auth::CheckUser, item::Item};
+use crate::api::blog::Blog;
+use crate::{Dba, DbAddr, PooledConn};
+
+// the same expressions as the GIN indexes, see migration search
+pub const ITEM_TSV: &str = "(\
+    setweight(to_tsvector('english', title), 'A') || \
+    setweight(to_tsvector('english', author || ' ' || link_host), 'B') || \
+    setweight(to_tsvector('english', content), 'C'))";
+pub const BLOG_TSV: &str = "(\
+    setweight(to_tsvector('english', aname), 'A') || \
+    setweight(to_tsvector('english', intro), 'B'))";
+
+pub const MAX_QUERY_LEN: usize = 128;
+pub const SEARCH_PERPAGE: i32 = 42;
+pub const BLOG_LIMIT: i64 = 8;
+
+// GET: /api/search?q=&topic=&ty=&page=
+//
+// w/ my_votes if signed in
+pub async fn search(
+    sq: Query<SearchQuery>,
+    auth: Option<CheckUser>,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let s = SearchQuery { uname: auth.map(|a| a.uname), ..sq.into_inner() };
+
+    if let Err(e) = s.validate() {
+        error!("{}", e);
+        return Ok(e.error_response());
+    }
+
+    let res = db.send(s).await?;
+    match res {
+        Ok(r) => Ok(HttpResponse::Ok().json(r)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<SearchQuery> for Dba {
+    type Result = ServiceResult<SearchResult>;
+
+    fn handle(&mut self, sq: SearchQuery, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        sq.search(conn)
+    }
+}
+
+// =================================================================================
+// =================================================================================
+// Model
+// =================================================================================
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct SearchQuery {
+    pub q: String,
+    pub topic: Option<String>,  // all if none
+    pub ty: Option<String>,
+    pub page: Option<i32>,
+    #[serde(skip)]
+    pub uname: Option<String>,  // the caller, for my_votes
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct SearchResult {
+    pub items: Vec<Item>,
+    pub blogs: Vec<Blog>,  // on the first page only
+    #[serde(default)]
+    pub my_votes: HashMap<i32, i16>,  // of the caller, by item id
+}
+
+impl SearchQuery {
+    // topic or type to filter, empty or all as none
+    pub fn topic(&self) -> Option<&str> {
+        self.topic.as_deref()
+            .map(|t| t.trim())
+            .filter(|t| t.len() > 0 && *t != "all")
+    }
+
+    pub fn ty(&self) -> Option<&str> {
+        self.ty.as_deref()
+            .map(|t| t.trim())
+            .filter(|t| t.len() > 0)
+    }
+
+    pub fn page(&self) -> i32 {
+        std::cmp::max(self.page.unwrap_or(1), 1)
+    }
+
+    fn search(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<SearchResult> {
+        let q = self.q.trim();
+        let page = self.page();
+
+        let items = {
+            use crate::schema::items::dsl::*;
+            let matched = sql::<Bool>(&format!("{} @@ plainto_tsquery('english', ", ITEM_TSV))
+                .bind::<Text, _>(q)
+                .sql(")");
+            let rank = sql::<Float>(&format!("ts_rank({}, plainto_tsquery('english', ", ITEM_TSV))
+                .bind::<Text, _>(q)
+                .sql("))");
+            let mut query = items
+                .filter(matched)
+                .filter(dup_of.is_null())
+                .filter(is_pending.eq(false))
+                .into_boxed();
+            if let Some(t) = self.topic() {
+                query = query.filter(topic.eq(t));
+            }
+            if let Some(t) = self.ty() {
+                query = query.filter(ty.eq(t));
+            }
+            query
+                .order((rank.desc(), post_at.desc()))
+                .limit(SEARCH_PERPAGE.into())
+                .offset(((page - 1) * SEARCH_PERPAGE).into())
+                .load::<Item>(conn)?
+        };
+
+        let blogs = if page == 1 {
+            use crate::schema::blogs::dsl::*;
+            let matched = sql::<Bool>(&format!("{} @@ plainto_tsquery('english', ", BLOG_TSV))
+                .bind::<Text, _>(q)
+                .sql(")");
+            let rank = sql::<Float>(&format!("ts_rank({}, plainto_tsquery('english', ", BLOG_TSV))
+                .bind::<Text, _>(q)
+                .sql("))");
+            let mut query = blogs.filter(matched).into_boxed();
+            if let Some(t) = self.topic() {
+                query = query.filter(topic.eq(t));
+            }
+            query
+                .order((rank.desc(), karma.desc()))
+                .limit(BLOG_LIMIT)
+                .load::<Blog>(conn)?
+        } else {
+            Vec::new()
+        };
+
+        let my_votes = match self.uname.as_ref() {
+            Some(u) => {
+                use crate::api::item::my_votes;
+                let ids: Vec<i32> = items.iter().map(|i| i.id).collect();
+                my_votes(conn, u, &ids)?
+            }
+            None => HashMap::new(),
+        };
+
+        Ok(SearchResult { items, blogs, my_votes })
+    }
+
+    pub fn validate(&self) -> ServiceResult<()> {
+        use crate::view::{TOPIC_VEC, TY_VEC};
+        let q = self.q.trim();
+        let check = q.len() > 0
+            && q.chars().count() <= MAX_QUERY_LEN
+            && self.topic().map(|t| TOPIC_VEC.contains(&t)).unwrap_or(true)
+            && self.ty().map(|t| TY_VEC.contains(&t)).unwrap_or(true);
+
+        if check {
+            Ok(())
+        } else {
+            Err(ServiceError::BadRequest("Invalid Search".into()))
+        }
+    }
+}
+
+impl Message for SearchQuery {
+    type Result = ServiceResult<SearchResult>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_search_query() {
+        let mut s = SearchQuery {
+            q: " async rust ".to_owned(),
+            topic: Some("all".to_owned()),
+            ty: Some("".to_owned()),
+            page: Some(0),
+            uname: None,
+        };
+        assert!(s.validate().is_ok());
+        assert_eq!(s.topic(), None);
+        assert_eq!(s.ty(), None);
+        assert_eq!(s.page(), 1);
+
+        s.topic = Some("Rust".to_owned());
+        s.ty = Some("Article".to_owned());
+        assert!(s.validate().is_ok());
+        assert_eq!(s.topic(), Some("Rust"));
+
+        s.ty = Some("Blog".to_owned());
+        assert!(s.validate().is_err());
+        s.ty = None;
+        s.q = "  ".to_owned();
+        assert!(s.validate().is_err());
+    }
+}
@@ -0,0 +1,451 @@
+// api.source: spider sources registry
+
+use actix::{Handler, Message};
+use actix_web::{
+    web::{Data, Json, Path},
+    Error, HttpResponse, ResponseError,
+    Result,
+};
+use diesel::prelude::*;
+use diesel::{self, ExpressionMethods, QueryDsl, RunQueryDsl};
+use chrono::NaiveDateTime;
+use log::error;
+use regex::Regex;
+use scraper::Selector;
+
+use crate::errors::{ServiceError, ServiceResult};
+use crate::api::{re_test_url, auth::CheckCan};
+use crate::bot::spider::get_author_topic;
+use crate::{Dba, DbAddr, PooledConn};
+use crate::schema::{sources};
+
+// POST: /api/sources
+//
+pub async fn new(
+    source: Json<NewSource>,
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let src = source.into_inner();
+
+    if let Err(e) = src.validate() {
+        error!("{}", e);
+        return Ok(e.error_response());
+    }
+
+    let res = db.send(src).await?;
+    match res {
+        Ok(s) => Ok(HttpResponse::Ok().json(s)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<NewSource> for Dba {
+    type Result = ServiceResult<Source>;
+
+    fn handle(&mut self, ns: NewSource, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        ns.new(conn)
+    }
+}
+
+// PUT: /api/sources
+//
+pub async fn update(
+    source: Json<UpdateSource>,
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let src = source.into_inner();
+
+    if let Err(e) = src.validate() {
+        error!("{}", e);
+        return Ok(e.error_response());
+    }
+
+    let res = db.send(src).await?;
+    match res {
+        Ok(s) => Ok(HttpResponse::Ok().json(s)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<UpdateSource> for Dba {
+    type Result = ServiceResult<Source>;
+
+    fn handle(&mut self, s: UpdateSource, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        s.update(conn)
+    }
+}
+
+// GET: /api/sources
+//
+pub async fn get_list(
+    _can: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let res = db.send(QuerySources::All).await?;
+    match res {
+        Ok(s) => Ok(HttpResponse::Ok().json(s)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<QuerySources> for Dba {
+    type Result = ServiceResult<Vec<Source>>;
+
+    fn handle(&mut self, qs: QuerySources, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        qs.get(conn)
+    }
+}
+
+// GET: /api/sources/{id}
+//
+pub async fn get(
+    qs: Path<i32>,
+    auth: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let src = QuerySource{
+        id: qs.into_inner(),
+        method: String::from("GET"),
+        uname: auth.uname
+    };
+    let res = db.send(src).await?;
+    match res {
+        Ok(s) => Ok(HttpResponse::Ok().json(s)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+// PUT: /api/sources/{id}
+//
+// enable or disable
+pub async fn toggle_enabled(
+    qs: Path<i32>,
+    auth: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let src = QuerySource{
+        id: qs.into_inner(),
+        method: String::from("PUT"),
+        uname: auth.uname
+    };
+    let res = db.send(src).await?;
+    match res {
+        Ok(s) => Ok(HttpResponse::Ok().json(s.is_enabled)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+// DELETE: /api/sources/{id}
+//
+pub async fn del(
+    qs: Path<i32>,
+    auth: CheckCan,
+    db: Data<DbAddr>,
+) -> ServiceResult<HttpResponse> {
+    let src = QuerySource{
+        id: qs.into_inner(),
+        method: String::from("DELETE"),
+        uname: auth.uname
+    };
+    let res = db.send(src).await?;
+    match res {
+        Ok(s) => Ok(HttpResponse::Ok().json(s.id)),
+        Err(e) => { error!("{}", e); Ok(e.error_response()) },
+    }
+}
+
+impl Handler<QuerySource> for Dba {
+    type Result = ServiceResult<Source>;
+
+    fn handle(&mut self, qs: QuerySource, _: &mut Self::Context) -> Self::Result {
+        let conn: &PooledConn = &self.0.get()?;
+        let method: &str = &qs.method.trim();
+
+        match method {
+            "GET" => { qs.get(conn) }
+            "PUT" => { qs.toggle_enabled(conn) }
+            "DELETE" => { qs.del(conn) }
+            _ => { qs.get(conn) },
+        }
+    }
+}
+
+
+// =================================================================================
+// =================================================================================
+// Model
+// =================================================================================
+
+#[derive(Clone, Debug, Serialize, Deserialize, Identifiable, Queryable)]
+#[table_name = "sources"]
+pub struct Source {
+    pub id: i32,
+    pub url: String,           // unique, the start url: a listing page or a feed
+    pub author: String,        // author of items, empty: guess via host
+    pub topic: String,         // topic of items, empty: guess via host
+    pub link_prefix: String,   // rules to pick links in page,
+    pub link_regex: String,    // all empty: per-domain rules in bot/cfg.rs
+    pub link_selector: String,
+    pub is_enabled: bool,
+    pub add_at: NaiveDateTime,
+    pub etag: String,          // validators of the last fetch,
+    pub last_modified: String, // for conditional GET
+}
+
+impl Source {
+    // if any rule defined
+    pub fn has_rule(&self) -> bool {
+        self.link_prefix.trim().len() > 0
+            || self.link_regex.trim().len() > 0
+            || self.link_selector.trim().len() > 0
+    }
+
+    // the topic set by admin, else classify the items
+    pub fn has_topic(&self) -> bool {
+        self.topic.trim().len() > 0
+    }
+
+    // source's author and topic as default for items, or guess via host
+    pub fn author_topic(&self, host: &str) -> (String, String) {
+        let (host_author, host_topic) = get_author_topic(host);
+        let author = if self.author.trim().len() > 0 {
+            self.author.trim().to_owned()
+        } else {
+            host_author
+        };
+        let topic = if self.topic.trim().len() > 0 {
+            self.topic.trim().to_owned()
+        } else {
+            host_topic
+        };
+        (author, topic)
+    }
+
+    // keep the validators if changed
+    pub fn save_validators(
+        &self,
+        conn: &PgConnection,
+        new_etag: &str,
+        new_last_modified: &str,
+    ) -> QueryResult<()> {
+        use crate::schema::sources::dsl::{sources, etag, last_modified};
+        if self.etag == new_etag && self.last_modified == new_last_modified {
+            return Ok(());
+        }
+        diesel::update(sources.find(self.id))
+            .set((
+                etag.eq(new_etag),
+                last_modified.eq(new_last_modified),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    // the enabled sources to feed spider
+    pub fn load_enabled(conn: &PgConnection) -> QueryResult<Vec<Source>> {
+        use crate::schema::sources::dsl::{sources, is_enabled, id};
+        sources
+            .filter(is_enabled.eq(true))
+            .order(id.asc())
+            .load::<Source>(conn)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default, Insertable)]
+#[table_name = "sources"]
+pub struct NewSource {
+    pub url: String,
+    pub author: String,
+    pub topic: String,
+    pub link_prefix: String,
+    pub link_regex: String,
+    pub link_selector: String,
+    pub is_enabled: bool,
+}
+
+impl NewSource {
+    fn new(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Source> {
+        use crate::schema::sources::dsl::{sources};
+        let new_source = NewSource {
+            url: self.url.trim().to_owned(),
+            author: self.author.trim().to_owned(),
+            topic: self.topic.trim().to_owned(),
+            link_prefix: self.link_prefix.trim().to_owned(),
+            link_regex: self.link_regex.trim().to_owned(),
+            link_selector: self.link_selector.trim().to_owned(),
+            is_enabled: self.is_enabled,
+        };
+        let source_new = diesel::insert_into(sources)
+            .values(&new_source)
+            .get_result::<Source>(conn)?;
+
+        Ok(source_new)
+    }
+
+    fn validate(&self) -> ServiceResult<()> {
+        validate_rules(
+            &self.url, &self.link_regex, &self.link_selector
+        )
+    }
+}
+
+impl Message for NewSource {
+    type Result = ServiceResult<Source>;
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default, AsChangeset)]
+#[table_name = "sources"]
+pub struct UpdateSource {
+    pub id: i32,
+    pub url: String,
+    pub author: String,
+    pub topic: String,
+    pub link_prefix: String,
+    pub link_regex: String,
+    pub link_selector: String,
+    pub is_enabled: bool,
+}
+
+impl UpdateSource {
+    fn update(
+        self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Source> {
+        use crate::schema::sources::dsl::*;
+        let old = sources.filter(id.eq(self.id))
+            .get_result::<Source>(conn)?;
+
+        let up = UpdateSource {
+            id: self.id,
+            url: self.url.trim().to_owned(),
+            author: self.author.trim().to_owned(),
+            topic: self.topic.trim().to_owned(),
+            link_prefix: self.link_prefix.trim().to_owned(),
+            link_regex: self.link_regex.trim().to_owned(),
+            link_selector: self.link_selector.trim().to_owned(),
+            is_enabled: self.is_enabled,
+        };
+
+        let mut source_update = diesel::update(&old)
+            .set(&up)
+            .get_result::<Source>(conn)?;
+
+        // validators are for the old url
+        if old.url != source_update.url {
+            source_update = diesel::update(&old)
+                .set((etag.eq(""), last_modified.eq("")))
+                .get_result::<Source>(conn)?;
+        }
+
+        Ok(source_update)
+    }
+
+    fn validate(&self) -> ServiceResult<()> {
+        validate_rules(
+            &self.url, &self.link_regex, &self.link_selector
+        )
+    }
+}
+
+impl Message for UpdateSource {
+    type Result = ServiceResult<Source>;
+}
+
+// check url, and the rules can be compiled
+fn validate_rules(
+    url: &str,
+    link_regex: &str,
+    link_selector: &str,
+) -> ServiceResult<()> {
+    let re = link_regex.trim();
+    let sel = link_selector.trim();
+    let check = re_test_url(url.trim())
+        && (re.len() == 0 || Regex::new(re).is_ok())
+        && (sel.len() == 0 || Selector::parse(sel).is_ok());
+
+    if check {
+        Ok(())
+    } else {
+        error!("source rule");
+        Err(ServiceError::BadRequest("Invalid Url or Rule".into()))
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct QuerySource {
+    pub id: i32,
+    pub method: String, // get|put|delete
+    pub uname: String,
+}
+
+impl QuerySource {
+    fn get(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Source> {
+        use crate::schema::sources::dsl::{sources, id};
+        let source = sources.filter(id.eq(self.id)).get_result::<Source>(conn)?;
+        Ok(source)
+    }
+
+    fn toggle_enabled(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Source> {
+        use crate::schema::sources::dsl::{sources, id, is_enabled};
+        let old = sources
+            .filter(id.eq(&self.id))
+            .get_result::<Source>(conn)?;
+        let source = diesel::update(&old)
+            .set(is_enabled.eq(!old.is_enabled))
+            .get_result::<Source>(conn)?;
+
+        Ok(source)
+    }
+
+    fn del(
+        &self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Source> {
+        use crate::schema::sources::dsl::{sources, id};
+        let source = diesel::delete(sources.filter(id.eq(self.id)))
+            .get_result::<Source>(conn)?;
+        Ok(source)
+    }
+}
+
+impl Message for QuerySource {
+    type Result = ServiceResult<Source>;
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub enum QuerySources {
+    All,
+    Enabled,
+}
+
+impl QuerySources {
+    pub fn get(
+        self,
+        conn: &PooledConn,
+    ) -> ServiceResult<Vec<Source>> {
+        use crate::schema::sources::dsl::*;
+        let source_list = match self {
+            QuerySources::Enabled => Source::load_enabled(conn)?,
+            QuerySources::All => sources.order(id.asc()).load::<Source>(conn)?,
+        };
+        Ok(source_list)
+    }
+}
+
+impl Message for QuerySources {
+    type Result = ServiceResult<Vec<Source>>;
+}
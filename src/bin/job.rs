@@ -15,6 +15,10 @@ fn main() -> SrvResult<()> {
         .enqueue(&conn)
         .map_err(|e| SrvError::from_std_error(e))?;
 
+    tasks::check_item_links()
+        .enqueue(&conn)
+        .map_err(|e| SrvError::from_std_error(e))?;
+
     tasks::gen_static_site()
         .enqueue(&conn)
         .map_err(|e| SrvError::from_std_error(e))?;
@@ -5,16 +5,8 @@ use srv::init_server;
 #[actix_rt::main]
 async fn main() -> std::io::Result<()> {
 
-    /*
-    // to test spider works
-    use srv::bot::spider::{WebPage};
-    let r = WebPage::new("https://levpaul.com/");
-    let links = dbg!(r.unwrap_or_default().clean_links());
-    println!("{:#?}", links);
-    let item = WebPage::new(&links[0]).unwrap_or_default().into_item();
-    println!("{:#?}", item);
-    // end
-    */
+    // to test spider rules, see the spider bin:
+    // cargo run --bin spider -- links <url>
 
     init_server().await
 }
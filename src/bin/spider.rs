@@ -0,0 +1,165 @@
+// spider bin, to write and check the rules of source w/o db
+//
+// spider links <url> [--file page.html] [--prefix /blog/] [--regex re] [--selector css]
+// spider item <url> [--file page.html]
+// spider snapshot <url> [--article <url>] [--dir tests/fixtures/spider] [--prefix ..]
+
+use srv::api::source::Source;
+use srv::bot::cfg::get_source_links;
+use srv::bot::spider::WebPage;
+#[cfg(feature = "fixture")]
+use srv::bot::fixture::{save_snapshot, SourceRules, FIXTURE_DIR};
+
+const USAGE: &str = "\
+Usage:
+    spider links <url> [--file <path>] [--prefix <p>] [--regex <re>] [--selector <css>]
+        print the links picked from a source page,
+        per the rules given, else the per-domain rules in cfg.rs
+    spider item <url> [--file <path>]
+        print the item built from an article page, or the items of a feed
+    spider snapshot <url> [--article <url>] [--dir <path>] [--prefix <p>] [--regex <re>] [--selector <css>]
+        save the source page and an article page, the first link if not given,
+        as a fixture of tests/spider_rules.rs, w/ the rules, links and fields got now
+
+Options:
+    --file <path>   read the html of url from a saved local file, no network
+    --dir <path>    the fixtures dir, default tests/fixtures/spider";
+
+#[derive(Default)]
+struct Args {
+    cmd: String,
+    url: String,
+    file: String,
+    prefix: String,
+    regex: String,
+    selector: String,
+    article: String,
+    dir: String,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut argv = std::env::args().skip(1);
+    let mut args = Args::default();
+    args.cmd = argv.next().unwrap_or_default();
+    while let Some(a) = argv.next() {
+        let opt = a.as_str();
+        match opt {
+            "--file" => args.file = opt_value(&mut argv, opt)?,
+            "--prefix" => args.prefix = opt_value(&mut argv, opt)?,
+            "--regex" => args.regex = opt_value(&mut argv, opt)?,
+            "--selector" => args.selector = opt_value(&mut argv, opt)?,
+            "--article" => args.article = opt_value(&mut argv, opt)?,
+            "--dir" => args.dir = opt_value(&mut argv, opt)?,
+            _ if opt.starts_with("--") => return Err(format!("unknown option {}", opt)),
+            _ if args.url.len() == 0 => args.url = opt.to_owned(),
+            _ => return Err(format!("unexpected argument {}", opt)),
+        }
+    }
+    if args.url.len() == 0 {
+        return Err("missing url".to_owned());
+    }
+    Ok(args)
+}
+
+fn opt_value(argv: &mut impl Iterator<Item = String>, opt: &str) -> Result<String, String> {
+    argv.next().ok_or(format!("missing value of {}", opt))
+}
+
+// the page from local file, or fetch it
+fn get_page(args: &Args) -> Result<WebPage, String> {
+    if args.file.len() > 0 {
+        let html = std::fs::read_to_string(&args.file)
+            .map_err(|e| format!("read {}: {}", args.file, e))?;
+        return Ok(WebPage::from_html(&args.url, html));
+    }
+    let page = WebPage::new(&args.url).map_err(|e| e.to_string())?;
+    if !page.is_ok() {
+        return Err(format!("fetch {}: {} {}", args.url, page.status, page.error));
+    }
+    Ok(page)
+}
+
+fn links(args: &Args) -> Result<(), String> {
+    let page = get_page(args)?;
+    let src = Source {
+        id: 0,
+        url: args.url.clone(),
+        author: String::new(),
+        topic: String::new(),
+        link_prefix: args.prefix.clone(),
+        link_regex: args.regex.clone(),
+        link_selector: args.selector.clone(),
+        is_enabled: true,
+        add_at: chrono::Utc::now().naive_utc(),
+        etag: String::new(),
+        last_modified: String::new(),
+    };
+    let links = if page.is_feed() {
+        page.into_feed_items().into_iter().map(|i| i.link).collect()
+    } else {
+        get_source_links(&page, &src)
+    };
+    for l in links.iter() {
+        println!("{}", l);
+    }
+    eprintln!("{} links", links.len());
+    Ok(())
+}
+
+fn item(args: &Args) -> Result<(), String> {
+    let page = get_page(args)?;
+    let json = if page.is_feed() {
+        serde_json::to_string_pretty(&page.into_feed_items())
+    } else {
+        serde_json::to_string_pretty(&page.into_item())
+    };
+    println!("{}", json.map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+#[cfg(feature = "fixture")]
+fn snapshot(args: &Args) -> Result<(), String> {
+    let source = get_page(args)?;
+    let rules = SourceRules {
+        prefix: args.prefix.clone(),
+        regex: args.regex.clone(),
+        selector: args.selector.clone(),
+    };
+    let article_url = if args.article.len() > 0 {
+        args.article.clone()
+    } else {
+        get_source_links(&source, &rules.source(&args.url))
+            .first()
+            .cloned()
+            .unwrap_or_default()
+    };
+    let article = if article_url.len() > 0 {
+        let a = Args { url: article_url, ..Default::default() };
+        Some(get_page(&a)?)
+    } else {
+        None
+    };
+    let root = if args.dir.len() > 0 { args.dir.as_str() } else { FIXTURE_DIR };
+    let dir = save_snapshot(std::path::Path::new(root), &source, &rules, article.as_ref())
+        .map_err(|e| e.to_string())?;
+    println!("saved to {}, review the expected links and fields", dir.display());
+    Ok(())
+}
+
+#[cfg(not(feature = "fixture"))]
+fn snapshot(_args: &Args) -> Result<(), String> {
+    Err("snapshot needs the feature fixture".to_owned())
+}
+
+fn main() {
+    let res = parse_args().and_then(|args| match args.cmd.as_str() {
+        "links" => links(&args),
+        "item" => item(&args),
+        "snapshot" => snapshot(&args),
+        _ => Err(format!("unknown command {}", args.cmd)),
+    });
+    if let Err(e) = res {
+        eprintln!("{}\n\n{}", e, USAGE);
+        std::process::exit(1);
+    }
+}
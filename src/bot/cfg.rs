@@ -1,9 +1,47 @@
 // cfg spider to get webpage links
 
 use std::collections::HashMap;
-use crate::bot::spider::WebPage;
+use regex::Regex;
+use crate::bot::spider::{WebPage, abs_link};
+use crate::api::source::Source;
 
-// 
+// pick links per the rules of source in db,
+// fallback to the per-domain rules below if no rule defined
+pub fn get_source_links(page: &WebPage, src: &Source) -> Vec<String> {
+    if !src.has_rule() {
+        return get_links(page);
+    }
+
+    let selector = src.link_selector.trim();
+    let sel = if selector.len() == 0 { "a" } else { selector };
+    let prefix = src.link_prefix.trim();
+    let re = match Regex::new(src.link_regex.trim()) {
+        Ok(r) => r,
+        _ => return Vec::new()
+    };
+
+    let mut links: Vec<String> = Vec::new();
+    for link in page.extract_links(sel) {
+        let f_link = abs_link(&page.url, link.trim());
+        // prefix can be relative or absolute
+        let check_prefix = prefix.len() == 0 
+            || link.starts_with(prefix) 
+            || f_link.starts_with(prefix);
+        if check_prefix && re.is_match(&f_link) 
+            && !(f_link.contains("/#")) 
+            && f_link != page.url 
+        {
+            links.push(f_link)
+        }
+    }
+
+    links.sort();
+    links.dedup();
+    links.reverse();
+    links
+}
+
+// per-domain rules
 
 pub fn get_links(page: &WebPage) -> Vec<String> {
     let domain = &page.domain;
@@ -349,6 +387,7 @@ pub fn get_links(page: &WebPage) -> Vec<String> {
 }
  
 // maintain a hashmap to map {host: (author, topic)}
+// the sources to feed spider are in db, table sources, via /api/sources
 lazy_static! {
     pub static ref MAP_HOST: HashMap<&'static str, (&'static str, &'static str)> = {
         let mut map = HashMap::new();
@@ -402,52 +441,4 @@ lazy_static! {
 
         map
     };
-
-    pub static ref LINK_VEC: Vec<&'static str> = {
-        vec!(
-            // ## Rust
-            "https://blog.rust-lang.org/",
-            "https://users.rust-lang.org/top",
-            "https://internals.rust-lang.org/top",
-            "http://smallcultfollowing.com/babysteps/", // babystep
-            "https://tokio.rs/blog/2019-11-tokio-0-2/", // tokio
-            "https://async.rs/blog/", // async-std
-            "https://blog.guillaume-gomez.fr/", // Gomez
-            "https://www.ralfj.de/blog/", // ralf
-            "https://fitzgeraldnick.com/", // fitz
-            "https://deterministic.space/", // pascal
-            "https://without.boats/blog/", // boats
-            "https://www.pietroalbini.org/", 
-            "https://fnordig.de/posts/",  // badboy
-            "https://kazlauskas.me/", // nagisa
-            "https://manishearth.github.io/", // mg
-            "https://www.ncameron.org/blog/",  // nrc
-            "https://blog.japaric.io/",  // jorge
-            "https://words.steveklabnik.com/",
-            "https://blog.digital-scurf.org/", // Daniel Silverstone
-            "https://blog.x5ff.xyz/blog/",  // Claus Matzinger
-            "https://llogiq.github.io/", // Andre Bogus
-            "https://tonyarcieri.com/",  // Tony Arcieri
-            "https://blog.yoshuawuyts.com/", // Yoshua Wuyts
-            "https://seanmonstar.com/",
-            "https://blog.ryanlevick.com/", // Ryan Levick
-            "https://matklad.github.io/",  // Aleksey Kladov
-            "https://blog.troutwine.us/", // 
-            "https://rust-embedded.github.io/blog/", // Embedded Rust Working Group
-            "https://rustacean-station.org/",
-            "https://raphlinus.github.io/", // Raph Levien
-            "https://lucumr.pocoo.org/",  //  Armin Ronacher
-            "https://stjepang.github.io/",  // Stjepan Glavina
-            "https://blog.bastion.rs/", // bastion.rs
-            "https://yaah.dev/",        // Jane Lusby
-            "https://levpaul.com/",
-            // cpp
-            "https://www.fluentcpp.com/",
-            // ## Golang
-            "https://blog.golang.org/index",
-            "https://research.swtch.com/",  // Russ Cox
-            // ## Web
-            "https://hacks.mozilla.org/",
-        )
-    };
 }
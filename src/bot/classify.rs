@@ -0,0 +1,197 @@
+// classify the topic and type of bot items via keyword weights,
+// the rules configured by admin, in db
+
+use diesel::prelude::*;
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::api::classify::ClassifyRule;
+use crate::api::item::NewItem;
+use crate::util::helper::get_host;
+use crate::view::{TOPIC_VEC, TY_VEC};
+
+// the weight of a keyword matched in each field
+const TITLE_WEIGHT: i32 = 3;
+const URL_WEIGHT: i32 = 2;
+const CONTENT_WEIGHT: i32 = 1;
+
+// count a keyword in content at most this times, the long text not dominate
+const MAX_HITS: usize = 3;
+
+// below it, no sure to tell
+pub const MIN_SCORE: i32 = 3;
+
+struct KwRule {
+    kind: String,
+    target: String,
+    weight: i32,
+    re: Regex,
+}
+
+pub struct Classifier {
+    rules: Vec<KwRule>,
+}
+
+impl Classifier {
+    // drop the rules w/ a target not in TOPIC_VEC or TY_VEC
+    pub fn new(rules: Vec<ClassifyRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter(|r| is_valid_target(&r.kind, &r.target))
+            .filter_map(|r| {
+                kw_regex(&r.kw).map(|re| KwRule {
+                    kind: r.kind,
+                    target: r.target,
+                    weight: r.weight,
+                    re,
+                })
+            })
+            .collect();
+        Classifier { rules }
+    }
+
+    pub fn load(conn: &PgConnection) -> QueryResult<Self> {
+        Ok(Classifier::new(ClassifyRule::load_all(conn)?))
+    }
+
+    pub fn topic_of(&self, title: &str, content: &str, url: &str) -> Option<String> {
+        self.best("topic", title, content, url)
+    }
+
+    pub fn ty_of(&self, title: &str, content: &str, url: &str) -> Option<String> {
+        self.best("ty", title, content, url)
+    }
+
+    // set type, and topic if not given explicitly;
+    // the keywords of page, if any, matched as the content
+    pub fn apply(&self, item: &mut NewItem, keywords: &[String], keep_topic: bool) {
+        let content = with_keywords(&item.content, keywords);
+        if let Some(t) = self.ty_of(&item.title, &content, &item.link) {
+            item.ty = t;
+        }
+        if keep_topic {
+            return;
+        }
+        if let Some(t) = self.topic_of(&item.title, &content, &item.link) {
+            item.topic = t;
+        }
+    }
+
+    // the target scored most, if high enough
+    fn best(&self, kind: &str, title: &str, content: &str, url: &str) -> Option<String> {
+        let title = title.to_lowercase();
+        let content = content.to_lowercase();
+        // words in host and path, w/o the scheme
+        let url = format!("{} {}", get_host(url), url)
+            .to_lowercase()
+            .replace(|c: char| c == '/' || c == '_', " ");
+
+        let mut scores: HashMap<&str, i32> = HashMap::new();
+        for r in self.rules.iter().filter(|r| r.kind == kind) {
+            let hits = TITLE_WEIGHT * r.re.find_iter(&title).take(1).count() as i32
+                + URL_WEIGHT * r.re.find_iter(&url).take(1).count() as i32
+                + CONTENT_WEIGHT * r.re.find_iter(&content).take(MAX_HITS).count() as i32;
+            if hits > 0 {
+                *scores.entry(r.target.as_str()).or_insert(0) += hits * r.weight;
+            }
+        }
+
+        // tie broken by the name, to be stable
+        scores
+            .into_iter()
+            .filter(|(_, s)| *s >= MIN_SCORE)
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+            .map(|(t, _)| t.to_owned())
+    }
+}
+
+// content and keywords as one text
+pub fn with_keywords(content: &str, keywords: &[String]) -> String {
+    if keywords.is_empty() {
+        return content.to_owned();
+    }
+    format!("{}\n{}", content, keywords.join(", "))
+}
+
+pub fn is_valid_target(kind: &str, target: &str) -> bool {
+    match kind {
+        "topic" => target != "all" && TOPIC_VEC.contains(&target),
+        "ty" => TY_VEC.contains(&target),
+        _ => false,
+    }
+}
+
+// match as a whole word or phrase, case-insensitive,
+// the word boundary only if starts or ends w/ a word char, e.g. c++, .net
+fn kw_regex(kw: &str) -> Option<Regex> {
+    let kw = kw.trim().to_lowercase();
+    if kw.len() == 0 {
+        return None;
+    }
+    let is_word = |c: Option<char>| c.map(|c| c.is_alphanumeric()).unwrap_or(false);
+    let head = if is_word(kw.chars().next()) { r"\b" } else { "" };
+    let tail = if is_word(kw.chars().last()) { r"\b" } else { "" };
+    let pattern = format!("{}{}{}", head, regex::escape(&kw), tail);
+    Regex::new(&pattern).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(kw: &str, kind: &str, target: &str, weight: i32) -> ClassifyRule {
+        ClassifyRule {
+            id: 0,
+            kw: kw.to_owned(),
+            kind: kind.to_owned(),
+            target: target.to_owned(),
+            weight,
+        }
+    }
+
+    #[test]
+    fn classify_by_keywords() {
+        let clf = Classifier::new(vec![
+            rule("rust", "topic", "Rust", 3),
+            rule("golang", "topic", "Go", 3),
+            rule("c++", "topic", "CPP", 3),
+            rule("java", "topic", "Java", 3),
+            rule("podcast", "ty", "Media", 3),
+            rule("episode", "ty", "Media", 2),
+            rule("we're hiring", "ty", "Job", 3),
+            rule("release", "ty", "Project", 1),
+            rule("golang", "topic", "all", 9),
+            rule("news", "ty", "News", 9),
+        ]);
+
+        let ep = "Episode 42: Async Rust with a Tokio maintainer";
+        assert_eq!(clf.ty_of(ep, "", "https://a.fm/42"), Some("Media".to_owned()));
+        assert_eq!(clf.topic_of(ep, "", "https://a.fm/42"), Some("Rust".to_owned()));
+
+        let job = "We're hiring a backend engineer";
+        let desc = "Join us to build the services in Golang.";
+        assert_eq!(clf.ty_of(job, desc, "https://b.io/jobs"), Some("Job".to_owned()));
+        assert_eq!(clf.topic_of(job, desc, "https://b.io/jobs"), Some("Go".to_owned()));
+
+        assert_eq!(
+            clf.ty_of("Serde 1.0.118 release notes", "", "https://c.dev/n"),
+            Some("Project".to_owned())
+        );
+        assert_eq!(
+            clf.topic_of("Modern C++ in practice", "", "https://d.dev/p"),
+            Some("CPP".to_owned())
+        );
+
+        // javascript is not java; a release in text only is not sure
+        let js = "Tips on JavaScript";
+        assert_eq!(clf.topic_of(js, "the next release", "https://e.dev/t"), None);
+        assert_eq!(clf.ty_of(js, "the next release", "https://e.dev/t"), None);
+
+        // the keywords of page tell, w/ a title not
+        let kws = vec!["golang".to_owned(), "concurrency".to_owned()];
+        let text = with_keywords("Notes on channels", &kws);
+        assert_eq!(text, "Notes on channels\ngolang, concurrency");
+        assert_eq!(clf.topic_of("Patterns I use", &text, "https://f.dev/p"), Some("Go".to_owned()));
+        assert_eq!(with_keywords("Notes", &[]), "Notes");
+    }
+}
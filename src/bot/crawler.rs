@@ -0,0 +1,541 @@
+// a concurrent and polite crawler
+//
+// bounded workers, per-host delay, request timeout, retry w/ backoff,
+// and honor robots.txt
+
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::error;
+use parking_lot::Mutex;
+use reqwest::blocking::Client;
+use reqwest::{header, StatusCode};
+
+use crate::errors::{ServiceError, ServiceResult};
+use crate::util::helper::get_host;
+use crate::bot::spider::WebPage;
+use crate::bot::linkcheck::LinkStatus;
+use crate::bot::robots::{Robots, BOT_NAME, url_origin, url_path};
+
+// stable, the token in it matched by robots.txt, see robots::BOT_NAME
+pub const USER_AGENT: &str =
+    "Mozilla/5.0 (compatible; toplogbot/0.1; +https://toplog.cc)";
+
+// refetch robots.txt after, or sooner if it was unreachable
+pub const ROBOTS_TTL: Duration = Duration::from_secs(12 * 3600);
+pub const ROBOTS_RETRY_TTL: Duration = Duration::from_secs(600);
+
+// redirects to follow when check a link
+pub const MAX_REDIRECTS: usize = 5;
+
+// the upper bound of retries and of the wait between
+pub const MAX_RETRIES: u32 = 5;
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+// url w/ the validators of last fetch, for conditional GET
+#[derive(Debug, Clone, Default)]
+pub struct CondReq {
+    pub url: String,
+    pub etag: String,
+    pub last_modified: String,
+}
+
+impl CondReq {
+    pub fn new(url: &str) -> Self {
+        CondReq {
+            url: url.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CrawlConfig {
+    pub concurrency: usize,    // max pages fetching at the same time
+    pub host_delay: Duration,  // min interval between 2 requests to a host
+    pub timeout: Duration,     // per request
+    pub retries: u32,          // retry times on timeout, 5xx, 429
+    pub backoff: Duration,     // base of the exponential backoff
+    pub user_agent: String,
+}
+
+impl Default for CrawlConfig {
+    fn default() -> Self {
+        CrawlConfig {
+            concurrency: 4,
+            host_delay: Duration::from_millis(1000),
+            timeout: Duration::from_secs(20),
+            retries: 2,
+            backoff: Duration::from_millis(500),
+            user_agent: USER_AGENT.to_owned(),
+        }
+    }
+}
+
+impl CrawlConfig {
+    // config via env, fallback to default
+    pub fn from_env() -> Self {
+        let d = CrawlConfig::default();
+        let num = |key: &str, default: u64| -> u64 {
+            dotenv::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(default)
+        };
+        CrawlConfig {
+            concurrency: num("CRAWL_CONCURRENCY", d.concurrency as u64).max(1) as usize,
+            host_delay: Duration::from_millis(
+                num("CRAWL_HOST_DELAY_MS", d.host_delay.as_millis() as u64)
+            ),
+            timeout: Duration::from_secs(
+                num("CRAWL_TIMEOUT_SECS", d.timeout.as_secs())
+            ),
+            retries: num("CRAWL_RETRIES", d.retries as u64).min(MAX_RETRIES as u64) as u32,
+            backoff: Duration::from_millis(
+                num("CRAWL_BACKOFF_MS", d.backoff.as_millis() as u64)
+            ),
+            user_agent: dotenv::var("CRAWL_USER_AGENT").unwrap_or(d.user_agent),
+        }
+    }
+
+    // base * 2^attempt, no more than MAX_BACKOFF
+    pub fn backoff_of(&self, attempt: u32) -> Duration {
+        2u32.checked_pow(attempt)
+            .and_then(|n| self.backoff.checked_mul(n))
+            .unwrap_or(MAX_BACKOFF)
+            .min(MAX_BACKOFF)
+    }
+}
+
+// the crawler shared by all spider work in a process
+lazy_static! {
+    pub static ref CRAWLER: Crawler = Crawler::new(CrawlConfig::from_env());
+}
+
+#[derive(Clone)]
+pub struct Crawler {
+    pub cfg: CrawlConfig,
+    client: Client,
+    // not follow redirects, to tell the moved and how
+    no_redirect: Client,
+    // host -> the time the next request allowed
+    hosts: Arc<Mutex<HashMap<String, Instant>>>,
+    // origin -> (robots.txt rules, expire time)
+    robots: Arc<Mutex<HashMap<String, (Arc<Robots>, Instant)>>>,
+}
+
+impl Crawler {
+    pub fn new(cfg: CrawlConfig) -> Self {
+        let client = Client::builder()
+            .timeout(cfg.timeout)
+            .user_agent(cfg.user_agent.as_str())
+            .build()
+            .unwrap_or_else(|e| {
+                error!("crawler client: {}", e);
+                Client::new()
+            });
+        let no_redirect = Client::builder()
+            .timeout(cfg.timeout)
+            .user_agent(cfg.user_agent.as_str())
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_else(|e| {
+                error!("crawler client: {}", e);
+                Client::new()
+            });
+        Crawler {
+            cfg,
+            client,
+            no_redirect,
+            hosts: Arc::new(Mutex::new(HashMap::new())),
+            robots: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // check robots.txt of the host
+    pub fn is_allowed(&self, url: &str) -> bool {
+        self.get_robots(url).allowed(&url_path(url))
+    }
+
+    // fetch a page, wait for the host and retry if need
+    pub fn fetch(&self, url: &str) -> ServiceResult<WebPage> {
+        self.fetch_cond(&CondReq::new(url))
+    }
+
+    // send If-None-Match / If-Modified-Since if any validator
+    pub fn fetch_cond(&self, req: &CondReq) -> ServiceResult<WebPage> {
+        let url = req.url.as_str();
+        let robots = self.get_robots(url);
+        if !robots.allowed(&url_path(url)) {
+            return Err(ServiceError::BadRequest(
+                format!("Disallowed by robots.txt: {}", url)
+            ));
+        }
+        let delay = robots.delay(self.cfg.host_delay);
+
+        let host = get_host(url);
+        let mut attempt: u32 = 0;
+        loop {
+            self.wait_host(&host, delay);
+            let mut request = self.client.get(url);
+            if req.etag.len() > 0 {
+                request = request.header(header::IF_NONE_MATCH, req.etag.as_str());
+            }
+            if req.last_modified.len() > 0 {
+                request = request
+                    .header(header::IF_MODIFIED_SINCE, req.last_modified.as_str());
+            }
+            let (retry_after, err) = match request.send() {
+                Ok(resp) => {
+                    let status = resp.status();
+                    if status == StatusCode::NOT_MODIFIED {
+                        return Ok(WebPage {
+                            status: status.as_u16(),
+                            etag: req.etag.clone(),
+                            last_modified: req.last_modified.clone(),
+                            ..WebPage::from_html(url, String::new())
+                        });
+                    }
+                    if status.is_success() {
+                        let etag = header_value(&resp, header::ETAG);
+                        let last_modified = header_value(&resp, header::LAST_MODIFIED);
+                        let html = resp.text()?;
+                        return Ok(WebPage {
+                            status: status.as_u16(),
+                            etag,
+                            last_modified,
+                            ..WebPage::from_html(url, html)
+                        });
+                    }
+                    if !should_retry(status) {
+                        return Ok(WebPage {
+                            status: status.as_u16(),
+                            error: format!("HTTP {}", status.as_u16()),
+                            ..WebPage::from_html(url, String::new())
+                        });
+                    }
+                    let after = resp
+                        .headers()
+                        .get(header::RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.trim().parse::<u64>().ok())
+                        .map(Duration::from_secs);
+                    (after, ServiceError::InternalServerError(
+                        format!("{} {}", status.as_u16(), url)
+                    ))
+                }
+                Err(e) => {
+                    // no need to retry a malformed request
+                    if e.is_builder() || e.is_redirect() {
+                        return Err(e.into());
+                    }
+                    (None, e.into())
+                }
+            };
+
+            if attempt >= self.cfg.retries {
+                return Err(err);
+            }
+            let backoff = self.cfg.backoff_of(attempt);
+            // honor Retry-After, but not wait too long
+            let wait = retry_after
+                .map(|a| a.min(self.cfg.timeout).max(backoff))
+                .unwrap_or(backoff);
+            thread::sleep(wait);
+            attempt += 1;
+        }
+    }
+
+    // fetch the raw bytes, an image or so, no more than limit
+    pub fn fetch_bytes(&self, url: &str, limit: u64) -> ServiceResult<Vec<u8>> {
+        let robots = self.get_robots(url);
+        if !robots.allowed(&url_path(url)) {
+            return Err(ServiceError::BadRequest(
+                format!("Disallowed by robots.txt: {}", url)
+            ));
+        }
+        self.wait_host(&get_host(url), robots.delay(self.cfg.host_delay));
+
+        let resp = self.client.get(url).send()?;
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(ServiceError::BadRequest(
+                format!("HTTP {} {}", status.as_u16(), url)
+            ));
+        }
+        let too_large = || ServiceError::BadRequest(format!("Too large: {}", url));
+        if resp.content_length().map(|l| l > limit).unwrap_or(false) {
+            return Err(too_large());
+        }
+        let mut bytes: Vec<u8> = Vec::new();
+        resp.take(limit + 1).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > limit {
+            return Err(too_large());
+        }
+
+        Ok(bytes)
+    }
+
+    // fetch pages concurrently, the pages in the same order as urls;
+    // an empty page if failed
+    pub fn crawl(&self, urls: Vec<String>) -> Vec<WebPage> {
+        let reqs = urls.iter().map(|u| CondReq::new(u)).collect();
+        self.crawl_cond(reqs)
+    }
+
+    // crawl w/ conditional GET
+    pub fn crawl_cond(&self, reqs: Vec<CondReq>) -> Vec<WebPage> {
+        self.run_workers(reqs, |crawler, req| {
+            let start = Instant::now();
+            let mut page = crawler.fetch_cond(req).unwrap_or_else(|e| {
+                error!("crawl {}: {}", req.url, e);
+                WebPage {
+                    error: e.to_string(),
+                    ..WebPage::from_html(&req.url, String::new())
+                }
+            });
+            page.duration_ms = start.elapsed().as_millis() as i32;
+            page
+        })
+    }
+
+    // check links concurrently, in the same order as urls
+    pub fn check_links(&self, urls: Vec<String>) -> Vec<LinkStatus> {
+        let reqs = urls.iter().map(|u| CondReq::new(u)).collect();
+        self.run_workers(reqs, |crawler, req| crawler.check_link(&req.url))
+    }
+
+    // HEAD, or GET if HEAD not supported well, and follow the redirects
+    pub fn check_link(&self, url: &str) -> LinkStatus {
+        let mut res = LinkStatus::new(url);
+        let robots = self.get_robots(url);
+        if !robots.allowed(&url_path(url)) {
+            res.blocked = true;
+            res.error = String::from("Disallowed by robots.txt");
+            return res;
+        }
+        let delay = robots.delay(self.cfg.host_delay);
+
+        let mut cur = url.to_owned();
+        let mut permanent = true;
+        for _ in 0..=MAX_REDIRECTS {
+            self.wait_host(&get_host(&cur), delay);
+            let head = self.no_redirect.head(&cur).send();
+            let resp = match head {
+                Ok(r) if r.status().is_success() || r.status().is_redirection() => Ok(r),
+                // some servers mishandle HEAD
+                _ => self.no_redirect.get(&cur).send(),
+            };
+            let resp = match resp {
+                Ok(r) => r,
+                Err(e) => {
+                    res.status = 0;
+                    res.error = e.to_string();
+                    return res;
+                }
+            };
+            let status = resp.status();
+            res.status = status.as_u16();
+            if !status.is_redirection() {
+                if cur != url {
+                    res.final_url = cur;
+                    res.permanent = permanent;
+                }
+                return res;
+            }
+            let next = resp
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|l| resp.url().join(l).ok());
+            match next {
+                Some(n) => {
+                    permanent = permanent && (
+                        status == StatusCode::MOVED_PERMANENTLY
+                        || status == StatusCode::PERMANENT_REDIRECT
+                    );
+                    cur = n.to_string();
+                }
+                None => {
+                    res.error = String::from("Redirect w/o location");
+                    return res;
+                }
+            }
+        }
+        res.error = String::from("Too many redirects");
+        res
+    }
+
+    // run the work on reqs w/ bounded workers, the results in the same order
+    fn run_workers<R, F>(&self, reqs: Vec<CondReq>, work: F) -> Vec<R>
+    where
+        R: Clone + Default + Send + 'static,
+        F: Fn(&Crawler, &CondReq) -> R + Send + Sync + 'static,
+    {
+        let total = reqs.len();
+        if total == 0 {
+            return Vec::new();
+        }
+        let work = Arc::new(work);
+
+        let queue = Arc::new(Mutex::new(interleave_hosts(reqs)));
+        let (tx, rx) = mpsc::channel();
+        let workers = self.cfg.concurrency.max(1).min(total);
+        let mut handles = Vec::new();
+        for _ in 0..workers {
+            let queue = queue.clone();
+            let tx = tx.clone();
+            let crawler = self.clone();
+            let work = work.clone();
+            handles.push(thread::spawn(move || loop {
+                let next = queue.lock().pop_front();
+                let (idx, req) = match next {
+                    Some(n) => n,
+                    None => break,
+                };
+                if tx.send((idx, work(&crawler, &req))).is_err() {
+                    break;
+                }
+            }));
+        }
+        drop(tx);
+
+        let mut results: Vec<R> = vec![R::default(); total];
+        for (idx, r) in rx {
+            results[idx] = r;
+        }
+        for h in handles {
+            let _ = h.join();
+        }
+
+        results
+    }
+
+    // cached robots.txt rules of the host, fetch if not yet or expired
+    fn get_robots(&self, url: &str) -> Arc<Robots> {
+        let origin = url_origin(url);
+        let now = Instant::now();
+        if let Some((r, expire)) = self.robots.lock().get(&origin) {
+            if *expire > now {
+                return r.clone();
+            }
+        }
+
+        self.wait_host(&get_host(url), self.cfg.host_delay);
+        let robots_url = format!("{}/robots.txt", origin);
+        let (robots, ttl) = match self.client.get(&robots_url).send() {
+            Ok(resp) if resp.status().is_success() => {
+                let txt = resp.text().unwrap_or_default();
+                (Robots::parse(&txt, BOT_NAME), ROBOTS_TTL)
+            }
+            // no robots.txt
+            Ok(resp) if resp.status().is_client_error() => {
+                (Robots::allow_all(), ROBOTS_TTL)
+            }
+            // server error or unreachable, keep off for a while
+            _ => {
+                error!("robots.txt unavailable: {}", robots_url);
+                (Robots::disallow_all(), ROBOTS_RETRY_TTL)
+            }
+        };
+        let robots = Arc::new(robots);
+        self.robots
+            .lock()
+            .insert(origin, (robots.clone(), Instant::now() + ttl));
+
+        robots
+    }
+
+    // reserve a slot of the host, then sleep till the slot
+    fn wait_host(&self, host: &str, delay: Duration) {
+        let now = Instant::now();
+        let slot = {
+            let mut hosts = self.hosts.lock();
+            let next = hosts.get(host).cloned().unwrap_or(now).max(now);
+            hosts.insert(host.to_owned(), next + delay);
+            next
+        };
+        if slot > now {
+            thread::sleep(slot - now);
+        }
+    }
+}
+
+fn header_value(
+    resp: &reqwest::blocking::Response,
+    name: header::HeaderName,
+) -> String {
+    resp.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .trim()
+        .to_owned()
+}
+
+fn should_retry(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+// round robin by host, so workers not queue up on one slow host
+fn interleave_hosts(reqs: Vec<CondReq>) -> VecDeque<(usize, CondReq)> {
+    let mut by_host: Vec<(String, VecDeque<(usize, CondReq)>)> = Vec::new();
+    for (idx, req) in reqs.into_iter().enumerate() {
+        let host = get_host(&req.url);
+        match by_host.iter_mut().find(|(h, _)| *h == host) {
+            Some((_, q)) => q.push_back((idx, req)),
+            None => {
+                let mut q = VecDeque::new();
+                q.push_back((idx, req));
+                by_host.push((host, q));
+            }
+        }
+    }
+
+    let mut queue = VecDeque::new();
+    loop {
+        let mut done = true;
+        for (_, q) in by_host.iter_mut() {
+            if let Some(u) = q.pop_front() {
+                queue.push_back(u);
+                done = false;
+            }
+        }
+        if done { break; }
+    }
+
+    queue
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_capped() {
+        let cfg = CrawlConfig::default();
+        assert_eq!(cfg.backoff_of(0), Duration::from_millis(500));
+        assert_eq!(cfg.backoff_of(2), Duration::from_millis(2000));
+        assert_eq!(cfg.backoff_of(10), MAX_BACKOFF);
+        assert_eq!(cfg.backoff_of(40), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn hosts_interleaved() {
+        let reqs = vec!(
+            CondReq::new("https://a.com/1"),
+            CondReq::new("https://a.com/2"),
+            CondReq::new("https://b.com/1"),
+            CondReq::new("https://www.a.com/3"),
+            CondReq::new("https://c.com/1"),
+        );
+        let order: Vec<usize> = interleave_hosts(reqs)
+            .into_iter()
+            .map(|(idx, _)| idx)
+            .collect();
+        assert_eq!(order, vec!(0, 2, 4, 1, 3));
+    }
+}
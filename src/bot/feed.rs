@@ -0,0 +1,223 @@
+// parse RSS 2.0 / Atom feed
+
+use chrono::{DateTime, NaiveDate};
+use scraper::Html;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct FeedEntry {
+    pub title: String,
+    pub link: String,
+    pub summary: String,
+    pub author: String,
+    pub pub_at: Option<NaiveDate>,
+}
+
+// check if the fetched text is a feed rather than a html page
+pub fn is_feed(text: &str) -> bool {
+    let head: String = text.trim_start().chars().take(1024).collect();
+    !head.to_lowercase().contains("<html")
+        && (head.contains("<rss")
+            || head.contains("<feed")
+            || head.contains("<rdf:RDF"))
+}
+
+// try RSS first, then Atom
+pub fn parse_feed(text: &str) -> Vec<FeedEntry> {
+    if let Ok(channel) = rss::Channel::read_from(text.as_bytes()) {
+        return parse_rss(&channel);
+    }
+    if let Ok(feed) = atom_syndication::Feed::read_from(text.as_bytes()) {
+        return parse_atom(&feed);
+    }
+
+    Vec::new()
+}
+
+fn parse_rss(channel: &rss::Channel) -> Vec<FeedEntry> {
+    let mut entries: Vec<FeedEntry> = Vec::new();
+    for item in channel.items() {
+        let link = match item.link() {
+            Some(l) => l.to_owned(),
+            None => match item.guid() {
+                Some(g) if g.is_permalink() => g.value().to_owned(),
+                _ => continue,
+            }
+        };
+        // author: <author> or <dc:creator>
+        let author = match item.author() {
+            Some(a) => a.to_owned(),
+            None => item
+                .dublin_core_ext()
+                .and_then(|dc| dc.creators().first().cloned())
+                .unwrap_or_default(),
+        };
+        // pub date: <pubDate> or <dc:date>
+        let pub_at = match item.pub_date() {
+            Some(d) => parse_feed_date(d),
+            None => item
+                .dublin_core_ext()
+                .and_then(|dc| dc.dates().first().cloned())
+                .and_then(|d| parse_feed_date(&d)),
+        };
+        let summary = item
+            .description()
+            .or(item.content())
+            .unwrap_or_default();
+
+        entries.push(FeedEntry {
+            title: item.title().unwrap_or(&link).to_owned(),
+            link,
+            summary: html_to_text(summary),
+            author: clean_author(&author),
+            pub_at,
+        });
+    }
+
+    entries
+}
+
+fn parse_atom(feed: &atom_syndication::Feed) -> Vec<FeedEntry> {
+    let mut entries: Vec<FeedEntry> = Vec::new();
+    // the feed level author applies to all entries w/o own author
+    let feed_author = feed
+        .authors()
+        .first()
+        .map(|p| p.name().to_owned())
+        .unwrap_or_default();
+    for entry in feed.entries() {
+        let links = entry.links();
+        let alternate = links
+            .iter()
+            .find(|l| l.rel() == "alternate")
+            .or(links.first());
+        let link = match alternate {
+            Some(l) => l.href().to_owned(),
+            None => continue,
+        };
+        let author = entry
+            .authors()
+            .first()
+            .map(|p| p.name().to_owned())
+            .unwrap_or(feed_author.clone());
+        let pub_at = entry
+            .published()
+            .unwrap_or(entry.updated())
+            .naive_utc()
+            .date();
+        let summary = match entry.summary() {
+            Some(s) => s.to_owned(),
+            None => entry
+                .content()
+                .and_then(|c| c.value())
+                .unwrap_or_default()
+                .to_owned(),
+        };
+
+        entries.push(FeedEntry {
+            title: entry.title().to_owned(),
+            link,
+            summary: html_to_text(&summary),
+            author: clean_author(&author),
+            pub_at: Some(pub_at),
+        });
+    }
+
+    entries
+}
+
+// RFC 2822 in RSS, RFC 3339 in Atom and dc:date, some feeds just a date
+pub fn parse_feed_date(s: &str) -> Option<NaiveDate> {
+    let s = s.trim();
+    if let Ok(d) = DateTime::parse_from_rfc2822(s) {
+        return Some(d.naive_utc().date());
+    }
+    if let Ok(d) = DateTime::parse_from_rfc3339(s) {
+        return Some(d.naive_utc().date());
+    }
+    let date_part: String = s.chars().take(10).collect();
+    NaiveDate::parse_from_str(&date_part, "%Y-%m-%d").ok()
+}
+
+// description is html often, keep text only and cut as a summary
+pub const SUMMARY_LEN: usize = 512;
+
+pub fn html_to_text(s: &str) -> String {
+    let frag = Html::parse_fragment(s);
+    let text: Vec<&str> = frag.root_element().text().collect();
+    let joined = text.join(" ");
+    let words: Vec<&str> = joined.split_whitespace().collect();
+    cut_text(&words.join(" "), SUMMARY_LEN)
+}
+
+pub fn cut_text(text: &str, len: usize) -> String {
+    if text.chars().count() > len {
+        let cut: String = text.chars().take(len).collect();
+        cut + "..."
+    } else {
+        text.to_owned()
+    }
+}
+
+// RSS <author> is an email: "a@b.com (Name)"
+fn clean_author(s: &str) -> String {
+    let s = s.trim();
+    match (s.find('('), s.rfind(')')) {
+        (Some(start), Some(end)) if start < end => s[start + 1..end].trim().to_owned(),
+        _ => s.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rss_feed() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0"><channel>
+              <title>Blog</title><link>https://a.com/</link><description>d</description>
+              <item>
+                <title>Hello</title>
+                <link>https://a.com/hello/</link>
+                <description>&lt;p&gt;Some &lt;b&gt;bold&lt;/b&gt; text&lt;/p&gt;</description>
+                <author>me@a.com (Jane Doe)</author>
+                <pubDate>Tue, 20 Oct 2020 10:00:00 +0000</pubDate>
+              </item>
+            </channel></rss>"#;
+        assert!(is_feed(xml));
+        let entries = parse_feed(xml);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "Hello");
+        assert_eq!(entries[0].link, "https://a.com/hello/");
+        assert_eq!(entries[0].summary, "Some bold text");
+        assert_eq!(entries[0].author, "Jane Doe");
+        assert_eq!(entries[0].pub_at, Some(NaiveDate::from_ymd(2020, 10, 20)));
+    }
+
+    #[test]
+    fn parse_atom_feed() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+              <title>Blog</title><id>urn:a</id><updated>2020-10-21T00:00:00Z</updated>
+              <author><name>John</name></author>
+              <entry>
+                <title>World</title><id>urn:a:1</id>
+                <link rel="alternate" href="https://a.com/world/"/>
+                <updated>2020-10-21T00:00:00Z</updated>
+                <published>2020-10-19T08:00:00+08:00</published>
+                <summary>Short</summary>
+              </entry>
+            </feed>"#;
+        assert!(is_feed(xml));
+        let entries = parse_feed(xml);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].link, "https://a.com/world/");
+        assert_eq!(entries[0].author, "John");
+        assert_eq!(entries[0].pub_at, Some(NaiveDate::from_ymd(2020, 10, 19)));
+    }
+
+    #[test]
+    fn html_page_is_not_feed() {
+        assert!(!is_feed("<!doctype html><html><head></head></html>"));
+    }
+}
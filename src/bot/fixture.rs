@@ -0,0 +1,225 @@
+// saved pages of sources, to check the crawl rules w/o network
+//
+// tests/fixtures/spider/<domain>/
+//   index.html    the source page, a listing of articles
+//   links.json    the url of source page, and the links expected
+//   article.html  an article page, optional
+//   article.json  the url of article, and the PageInfo fields expected
+//
+// shared by tests/spider_rules.rs and the snapshot of bin spider,
+// w/ the feature fixture, on by default, off for the server build
+
+use chrono::NaiveDate;
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::api::source::Source;
+use crate::bot::cfg::get_source_links;
+use crate::bot::spider::{get_pub_date, parse_common_page, parse_ld_meta, PageInfo, WebPage};
+use crate::util::helper::get_host;
+
+pub const FIXTURE_DIR: &str = "tests/fixtures/spider";
+
+// the rules of source as in db, all empty: the per-domain rules in cfg.rs
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct SourceRules {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub prefix: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub regex: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub selector: String,
+}
+
+impl SourceRules {
+    pub fn source(&self, url: &str) -> Source {
+        Source {
+            id: 0,
+            url: url.to_owned(),
+            author: String::new(),
+            topic: String::new(),
+            link_prefix: self.prefix.clone(),
+            link_regex: self.regex.clone(),
+            link_selector: self.selector.clone(),
+            is_enabled: true,
+            add_at: chrono::Utc::now().naive_utc(),
+            etag: String::new(),
+            last_modified: String::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ExpectedLinks {
+    pub url: String,
+    #[serde(flatten)]
+    pub rules: SourceRules,
+    pub links: Vec<String>,
+}
+
+// the fields to check, the missing ones are skipped
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ExpectedPage {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub img: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pub_at: Option<NaiveDate>,  // only if found in page, else today
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word_count: Option<usize>,
+}
+
+impl ExpectedPage {
+    // all fields of the page as expected
+    pub fn from_page(page: &WebPage) -> Self {
+        let info = parse_common_page(page.get_html(), &page.url);
+        let html = page.get_html();
+        let ld = parse_ld_meta(&html);
+        let pub_at = get_pub_date(&html, &ld, &info.url)
+            .or_else(|| get_pub_date(&html, &ld, &page.url));
+        ExpectedPage {
+            url: page.url.clone(),
+            title: Some(info.title),
+            canonical: Some(info.url),
+            img: Some(info.img),
+            content: Some(info.content),
+            pub_at,
+            author: Some(info.author),
+            keywords: Some(info.keywords),
+            word_count: Some(info.word_count),
+        }
+    }
+
+    // the fields not as expected: (field, expected, got)
+    pub fn diff(&self, info: &PageInfo) -> Vec<(String, String, String)> {
+        let mut diffs = Vec::new();
+        let mut check = |field: &str, expected: Option<String>, got: String| {
+            if let Some(e) = expected {
+                if e != got {
+                    diffs.push((field.to_owned(), e, got));
+                }
+            }
+        };
+        check("title", self.title.clone(), info.title.clone());
+        check("canonical", self.canonical.clone(), info.url.clone());
+        check("img", self.img.clone(), info.img.clone());
+        check("content", self.content.clone(), info.content.clone());
+        check("pub_at", self.pub_at.map(|d| d.to_string()), info.pub_at.to_string());
+        check("author", self.author.clone(), info.author.clone());
+        check(
+            "keywords",
+            self.keywords.as_ref().map(|k| k.join(", ")),
+            info.keywords.join(", "),
+        );
+        check(
+            "word_count",
+            self.word_count.map(|n| n.to_string()),
+            info.word_count.to_string(),
+        );
+        diffs
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub dir: PathBuf,
+}
+
+impl Fixture {
+    // a fixture per sub dir
+    pub fn list(root: &Path) -> io::Result<Vec<Fixture>> {
+        let mut fixtures: Vec<Fixture> = Vec::new();
+        for entry in fs::read_dir(root)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                fixtures.push(Fixture { dir: path });
+            }
+        }
+        fixtures.sort_by(|a, b| a.dir.cmp(&b.dir));
+        Ok(fixtures)
+    }
+
+    pub fn name(&self) -> String {
+        self.dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    // the source page, as in db, and the links expected
+    pub fn source(&self) -> io::Result<Option<(WebPage, Source, Vec<String>)>> {
+        let expected: ExpectedLinks = match self.read_json("links.json")? {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+        let html = fs::read_to_string(self.dir.join("index.html"))?;
+        let src = expected.rules.source(&expected.url);
+        Ok(Some((WebPage::from_html(&expected.url, html), src, expected.links)))
+    }
+
+    // the article page and the fields expected
+    pub fn article(&self) -> io::Result<Option<(WebPage, ExpectedPage)>> {
+        let expected: ExpectedPage = match self.read_json("article.json")? {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+        let html = fs::read_to_string(self.dir.join("article.html"))?;
+        Ok(Some((WebPage::from_html(&expected.url, html), expected)))
+    }
+
+    fn read_json<T: serde::de::DeserializeOwned>(&self, file: &str) -> io::Result<Option<T>> {
+        let path = self.dir.join(file);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(path)?;
+        let v = serde_json::from_str(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Some(v))
+    }
+}
+
+// save the pages as a fixture, the links and fields got now as expected;
+// review the diff before commit, it is what the tests check against
+pub fn save_snapshot(
+    root: &Path,
+    source: &WebPage,
+    rules: &SourceRules,
+    article: Option<&WebPage>,
+) -> io::Result<PathBuf> {
+    let dir = root.join(get_host(&source.url));
+    fs::create_dir_all(&dir)?;
+
+    let links = ExpectedLinks {
+        url: source.url.clone(),
+        rules: rules.clone(),
+        links: get_source_links(source, &rules.source(&source.url)),
+    };
+    fs::write(dir.join("index.html"), &source.html)?;
+    write_json(&dir.join("links.json"), &links)?;
+
+    if let Some(page) = article {
+        fs::write(dir.join("article.html"), &page.html)?;
+        write_json(&dir.join("article.json"), &ExpectedPage::from_page(page))?;
+    }
+
+    Ok(dir)
+}
+
+fn write_json<T: serde::Serialize>(path: &Path, v: &T) -> io::Result<()> {
+    let text = serde_json::to_string_pretty(v)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, text + "\n")
+}
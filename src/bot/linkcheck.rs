@@ -0,0 +1,142 @@
+// check the links of items: flag the dead, follow the moved
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::schema::link_checks;
+use crate::util::helper::canonical_url;
+
+// links to check per run
+pub const CHECK_BATCH: i64 = 200;
+
+// recheck a link after
+pub const CHECK_EVERY_DAYS: i64 = 7;
+
+// dead in this many checks in a row to flag the item
+pub const DEAD_FAILS: i32 = 3;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkStatus {
+    pub url: String,
+    pub status: u16,         // of the final response, 0: no response
+    pub final_url: String,   // redirected to, empty if not
+    pub permanent: bool,     // all the redirects are 301 or 308
+    pub blocked: bool,       // disallowed by robots.txt, not checked
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Alive,
+    Moved(String),  // the new url, redirected to
+    Dead,
+    Unknown,        // blocked, no response, 5xx, 401, 403, 429.., not sure
+}
+
+impl LinkStatus {
+    pub fn new(url: &str) -> Self {
+        LinkStatus {
+            url: url.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        if self.blocked {
+            return Verdict::Unknown;
+        }
+        match self.status {
+            200..=299 => {
+                // compare in canonical form, but keep the real target
+                if self.permanent
+                    && self.final_url.len() > 0
+                    && canonical_url(&self.final_url) != canonical_url(&self.url)
+                {
+                    Verdict::Moved(self.final_url.clone())
+                } else {
+                    Verdict::Alive
+                }
+            }
+            404 | 410 => Verdict::Dead,
+            // 0: timeout, dns, or the checker offline, not the link's fault
+            _ => Verdict::Unknown,
+        }
+    }
+}
+
+// the last check of an item's link
+#[derive(Clone, Debug, Serialize, Deserialize, Queryable, Insertable, AsChangeset)]
+#[table_name = "link_checks"]
+pub struct LinkCheck {
+    pub item_id: i32,
+    pub url: String,
+    pub status: i32,
+    pub final_url: String,
+    pub permanent: bool,
+    pub error: String,
+    pub fails: i32,             // dead in the checks in a row
+    pub check_at: NaiveDateTime,
+}
+
+impl LinkCheck {
+    pub fn new(
+        item_id: i32,
+        res: &LinkStatus,
+        fails: i32,
+        check_at: NaiveDateTime,
+    ) -> Self {
+        LinkCheck {
+            item_id,
+            url: res.url.clone(),
+            status: res.status as i32,
+            final_url: res.final_url.clone(),
+            permanent: res.permanent,
+            error: res.error.clone(),
+            fails,
+            check_at,
+        }
+    }
+
+    pub fn save(&self, conn: &PgConnection) -> QueryResult<usize> {
+        use crate::schema::link_checks::dsl::{link_checks, item_id};
+        diesel::insert_into(link_checks)
+            .values(self)
+            .on_conflict(item_id)
+            .do_update()
+            .set(self)
+            .execute(conn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdict_of_link() {
+        let mut s = LinkStatus::new("https://a.com/post");
+        s.status = 200;
+        assert_eq!(s.verdict(), Verdict::Alive);
+
+        // a temporary redirect, or to the same page
+        s.final_url = "https://b.com/post".to_owned();
+        assert_eq!(s.verdict(), Verdict::Alive);
+        s.permanent = true;
+        s.final_url = "https://www.a.com/post/".to_owned();
+        assert_eq!(s.verdict(), Verdict::Alive);
+        s.final_url = "https://b.com/post".to_owned();
+        assert_eq!(s.verdict(), Verdict::Moved("https://b.com/post".to_owned()));
+        // the http or www only host kept as is
+        s.final_url = "http://www.b.com/post/".to_owned();
+        assert_eq!(s.verdict(), Verdict::Moved("http://www.b.com/post/".to_owned()));
+
+        s.status = 410;
+        assert_eq!(s.verdict(), Verdict::Dead);
+        s.status = 503;
+        assert_eq!(s.verdict(), Verdict::Unknown);
+        s.status = 0;
+        assert_eq!(s.verdict(), Verdict::Unknown);
+        s.blocked = true;
+        assert_eq!(s.verdict(), Verdict::Unknown);
+    }
+}
@@ -1,5 +1,13 @@
 
 pub mod jobs;
 pub mod spider;
+pub mod crawler;
+pub mod robots;
+pub mod seen;
 pub mod cfg;
+pub mod feed;
 pub mod tasks;
+pub mod classify;
+pub mod linkcheck;
+#[cfg(feature = "fixture")]
+pub mod fixture;
@@ -0,0 +1,234 @@
+// parse robots.txt and check if a path allowed for the bot
+
+use std::time::Duration;
+use regex::Regex;
+
+// the product token to match User-agent lines in robots.txt
+pub const BOT_NAME: &str = "toplogbot";
+
+// avoid a silly Crawl-delay hang the crawl
+pub const MAX_CRAWL_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub allow: bool,
+    pub path: String,
+    re: Regex,
+}
+
+impl Rule {
+    fn new(allow: bool, path: &str) -> Option<Self> {
+        // * matches any chars, $ at the end anchors
+        let anchored = path.ends_with('$');
+        let body = path.trim_end_matches('$');
+        let pattern = body
+            .split('*')
+            .map(|p| regex::escape(p))
+            .collect::<Vec<String>>()
+            .join(".*");
+        let pattern = if anchored {
+            format!("^{}$", pattern)
+        } else {
+            format!("^{}", pattern)
+        };
+        Regex::new(&pattern).ok().map(|re| Rule {
+            allow,
+            path: path.to_owned(),
+            re,
+        })
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        self.re.is_match(path)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<f64>,
+}
+
+// the rules apply to the bot
+#[derive(Debug, Clone, Default)]
+pub struct Robots {
+    pub rules: Vec<Rule>,
+    pub crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    // no robots.txt, or 4xx
+    pub fn allow_all() -> Self {
+        Robots::default()
+    }
+
+    // robots.txt unreachable or 5xx
+    pub fn disallow_all() -> Self {
+        Robots {
+            rules: Rule::new(false, "/").into_iter().collect(),
+            crawl_delay: None,
+        }
+    }
+
+    // pick the groups for the bot, or the groups for *
+    pub fn parse(text: &str, agent: &str) -> Self {
+        let agent = product_token(agent);
+        let mut groups: Vec<Group> = Vec::new();
+        let mut cur = Group::default();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut kv = line.splitn(2, ':');
+            let key = kv.next().unwrap_or("").trim().to_lowercase();
+            let val = kv.next().unwrap_or("").trim();
+            match key.as_str() {
+                "user-agent" => {
+                    // User-agent after rules starts a new group
+                    if cur.rules.len() > 0 || cur.crawl_delay.is_some() {
+                        groups.push(cur);
+                        cur = Group::default();
+                    }
+                    cur.agents.push(product_token(val));
+                }
+                "allow" | "disallow" if cur.agents.len() > 0 => {
+                    // an empty Disallow allows all
+                    if val.len() > 0 {
+                        if let Some(r) = Rule::new(key == "allow", val) {
+                            cur.rules.push(r);
+                        }
+                    }
+                }
+                "crawl-delay" if cur.agents.len() > 0 => {
+                    cur.crawl_delay = val.parse::<f64>().ok().filter(|d| *d >= 0.0);
+                }
+                _ => {}
+            }
+        }
+        if cur.agents.len() > 0 {
+            groups.push(cur);
+        }
+
+        // the same token, not a part of it, RFC 9309
+        let is_for_bot = |g: &&Group| {
+            g.agents.iter().any(|a| a != "*" && a.len() > 0 && *a == agent)
+        };
+        let is_for_all = |g: &&Group| g.agents.iter().any(|a| a == "*");
+        let matched: Vec<&Group> = if groups.iter().any(|g| is_for_bot(&g)) {
+            groups.iter().filter(is_for_bot).collect()
+        } else {
+            groups.iter().filter(is_for_all).collect()
+        };
+
+        let mut robots = Robots::default();
+        for g in matched {
+            robots.rules.extend(g.rules.iter().cloned());
+            if let Some(d) = g.crawl_delay {
+                robots.crawl_delay = Some(
+                    Duration::from_millis((d * 1000.0) as u64).min(MAX_CRAWL_DELAY)
+                );
+            }
+        }
+
+        robots
+    }
+
+    // the longest matched rule wins, Allow wins if tie
+    pub fn allowed(&self, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        let mut best: Option<&Rule> = None;
+        for r in self.rules.iter().filter(|r| r.matches(path)) {
+            best = match best {
+                Some(b) if b.path.len() > r.path.len() => Some(b),
+                Some(b) if b.path.len() == r.path.len() && b.allow => Some(b),
+                _ => Some(r),
+            };
+        }
+        best.map(|r| r.allow).unwrap_or(true)
+    }
+
+    // the delay between 2 requests to the host
+    pub fn delay(&self, default: Duration) -> Duration {
+        self.crawl_delay.unwrap_or(default).max(default)
+    }
+}
+
+// the name w/o version, lowercased: "TopLogBot/1.0" -> "toplogbot"
+fn product_token(agent: &str) -> String {
+    agent
+        .trim()
+        .split(|c: char| c == '/' || c.is_whitespace())
+        .next()
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+lazy_static! {
+    static ref Origin_re: Regex = Regex::new(r"^https?://[^/?#]+").unwrap();
+}
+
+// the path and query of url, to match the rules
+pub fn url_path(url: &str) -> String {
+    let rest = Origin_re.replace(url, "");
+    let path = rest.split('#').next().unwrap_or("");
+    if path.starts_with('/') {
+        path.to_owned()
+    } else {
+        format!("/{}", path)
+    }
+}
+
+// scheme and host, the robots.txt at its root
+pub fn url_origin(url: &str) -> String {
+    Origin_re
+        .find(url)
+        .map(|m| m.as_str().to_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXT: &str = "
+        # comment
+        User-agent: *
+        Disallow: /private/
+        Allow: /private/open.html
+        Disallow: /*.pdf$
+        Crawl-delay: 2
+
+        User-agent: badbot
+        Disallow: /
+    ";
+
+    #[test]
+    fn parse_robots() {
+        let r = Robots::parse(TXT, BOT_NAME);
+        assert!(r.allowed("/"));
+        assert!(r.allowed("/blog/post"));
+        assert!(!r.allowed("/private/a.html"));
+        assert!(r.allowed("/private/open.html"));
+        assert!(!r.allowed("/files/a.pdf"));
+        assert!(r.allowed("/files/a.pdf?v=1"));
+        assert_eq!(r.crawl_delay, Some(Duration::from_secs(2)));
+
+        let bad = Robots::parse(TXT, "BadBot");
+        assert!(!bad.allowed("/blog/post"));
+        assert!(bad.allowed("/robots.txt"));
+
+        // a part of the token is not for the bot
+        let part = "User-agent: bot\nDisallow: /\n\nUser-agent: log\nDisallow: /\n";
+        assert!(Robots::parse(part, BOT_NAME).allowed("/blog/post"));
+        let exact = "User-agent: TopLogBot/1.0\nDisallow: /\n";
+        assert!(!Robots::parse(exact, BOT_NAME).allowed("/blog/post"));
+    }
+
+    #[test]
+    fn path_of_url() {
+        assert_eq!(url_path("https://a.com"), "/");
+        assert_eq!(url_path("https://a.com/x/y?p=1#top"), "/x/y?p=1");
+        assert_eq!(url_origin("http://www.a.com/x"), "http://www.a.com");
+    }
+}
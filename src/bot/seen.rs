@@ -0,0 +1,98 @@
+// the urls seen by bot, in db
+
+use chrono::NaiveDateTime;
+use diesel::dsl::{any, now};
+use diesel::prelude::*;
+use std::collections::HashSet;
+
+use crate::schema::crawled_urls;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Queryable)]
+pub struct CrawledUrl {
+    pub url: String,
+    pub first_seen: NaiveDateTime,
+    pub last_checked: NaiveDateTime,
+    pub is_rejected: bool,
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[table_name = "crawled_urls"]
+struct NewCrawledUrl<'a> {
+    url: &'a str,
+}
+
+// keep the urls not seen yet, in order, w/o repeat
+pub fn filter_unseen(
+    conn: &PgConnection,
+    urls: Vec<String>,
+) -> QueryResult<Vec<String>> {
+    use crate::schema::crawled_urls::dsl::{crawled_urls, url};
+    if urls.len() == 0 {
+        return Ok(urls);
+    }
+    let seen: HashSet<String> = crawled_urls
+        .filter(url.eq(any(&urls)))
+        .select(url)
+        .load::<String>(conn)?
+        .into_iter()
+        .collect();
+
+    let mut unseen: Vec<String> = Vec::new();
+    for u in urls {
+        if !seen.contains(&u) && !unseen.contains(&u) {
+            unseen.push(u);
+        }
+    }
+    Ok(unseen)
+}
+
+// add new urls, or refresh the last_checked if seen
+pub fn add_urls(conn: &PgConnection, urls: &[String]) -> QueryResult<usize> {
+    use crate::schema::crawled_urls::dsl::{crawled_urls, url, last_checked};
+    let new_urls: Vec<NewCrawledUrl> = urls
+        .iter()
+        .map(|u| u.trim())
+        .filter(|u| u.len() > 0)
+        .map(|u| NewCrawledUrl { url: u })
+        .collect();
+    if new_urls.len() == 0 {
+        return Ok(0);
+    }
+    diesel::insert_into(crawled_urls)
+        .values(&new_urls)
+        .on_conflict(url)
+        .do_update()
+        .set(last_checked.eq(now))
+        .execute(conn)
+}
+
+// the seen urls found again
+pub fn touch_urls(conn: &PgConnection, urls: &[String]) -> QueryResult<usize> {
+    use crate::schema::crawled_urls::dsl::{crawled_urls, url, last_checked};
+    if urls.len() == 0 {
+        return Ok(0);
+    }
+    diesel::update(crawled_urls.filter(url.eq(any(urls))))
+        .set(last_checked.eq(now))
+        .execute(conn)
+}
+
+// rejected by admin, seen as well, never crawled again
+pub fn reject_urls(conn: &PgConnection, urls: &[String]) -> QueryResult<usize> {
+    use crate::schema::crawled_urls::dsl::{crawled_urls, url, last_checked, is_rejected};
+    let new_urls: Vec<NewCrawledUrl> = urls
+        .iter()
+        .map(|u| u.trim())
+        .filter(|u| u.len() > 0)
+        .map(|u| NewCrawledUrl { url: u })
+        .collect();
+    if new_urls.len() == 0 {
+        return Ok(0);
+    }
+    diesel::insert_into(crawled_urls)
+        .values(&new_urls)
+        .on_conflict(url)
+        .do_update()
+        .set((last_checked.eq(now), is_rejected.eq(true)))
+        .execute(conn)
+}
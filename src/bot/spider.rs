@@ -2,13 +2,16 @@
 
 use regex::Regex;
 use chrono::{NaiveDate, Utc};
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
+use log::error;
 
 use crate::errors::{ServiceError, ServiceResult};
 use crate::api::item::NewItem;
 use crate::api::{re_test_img_url, replace_sep, trim_url_qry};
-use crate::util::helper::gen_slug;
+use crate::util::helper::{canonical_url, gen_slug, get_host};
+use crate::bot::crawler::CRAWLER;
 use crate::bot::cfg::{get_links, MAP_HOST};
+use crate::bot::feed::{is_feed, parse_feed, parse_feed_date, cut_text, SUMMARY_LEN};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PageInfo {
@@ -16,6 +19,20 @@ pub struct PageInfo {
     pub url: String,
     pub img: String,
     pub content: String,
+    pub pub_at: NaiveDate,
+    pub author: String,         // empty if not found in page
+    pub keywords: Vec<String>,
+    pub word_count: usize,      // of the main content
+}
+
+// schema.org Article / BlogPosting in JSON-LD
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct LdMeta {
+    pub headline: String,
+    pub author: String,
+    pub image: String,
+    pub keywords: Vec<String>,
+    pub date_published: Option<NaiveDate>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -23,39 +40,51 @@ pub struct WebPage {
     pub url: String,
     pub html: String,
     pub domain: String,
+    pub status: u16,            // 0 if fetch failed
+    pub etag: String,
+    pub last_modified: String,
+    pub error: String,          // why failed
+    pub duration_ms: i32,
 }
 
 impl WebPage {
+    // fetch via the shared crawler: robots.txt, timeout, retry, per-host delay
     pub fn new(url: &str) -> ServiceResult<Self> {
-        // let res = reqwest::get(url)?.text()?;
-
-        let default_html = String::from("");
-        let res = match reqwest::blocking::get(url) {
-            Ok(resp) => {
-                let mut resp = resp;
-                match resp.text() {
-                    Ok(s) => s,
-                    _ => default_html
+        if !CRAWLER.is_allowed(url) {
+            error!("disallowed by robots.txt: {}", url);
+            return Err(ServiceError::BadRequest("Disallowed by robots.txt".into()));
+        }
+        let page = match CRAWLER.fetch(url) {
+            Ok(p) => p,
+            Err(e) => {
+                error!("fetch {}: {}", url, e);
+                WebPage {
+                    error: e.to_string(),
+                    ..WebPage::from_html(url, String::new())
                 }
-            },
-            _ => default_html
+            }
         };
+        Ok(page)
+    }
 
-        lazy_static! {
-            static ref Scheme_re: Regex = Regex::new(r"https?://").unwrap();
-            static ref Path_re: Regex = Regex::new(r"/.*").unwrap();
+    // build from the fetched html
+    pub fn from_html(url: &str, html: String) -> Self {
+        Self {
+            url: url.to_string(),
+            html,
+            domain: get_host(url),
+            ..Default::default()
         }
+    }
 
-        let uri = Scheme_re.replace_all(url, "");
-        let host = Path_re.replace_all(&uri, "");
-        let domain = host.replace("www.", "");
+    // 304 to a conditional GET, nothing new
+    pub fn is_not_modified(&self) -> bool {
+        self.status == 304
+    }
 
-        let page = Self {
-            url: url.to_string(),
-            html: res,
-            domain,
-        };
-        Ok(page)
+    // fetched w/ 2xx
+    pub fn is_ok(&self) -> bool {
+        self.status >= 200 && self.status < 300
     }
 
     // URL getter
@@ -99,28 +128,80 @@ impl WebPage {
         get_links(self)
     }
 
+    // if the source is a RSS or Atom feed
+    pub fn is_feed(&self) -> bool {
+        is_feed(&self.html)
+    }
+
+    // build items from feed entries directly, no need to spider each link
+    pub fn into_feed_items(&self) -> Vec<NewItem> {
+        let (host_author, host_topic) = get_author_topic(self.domain.trim());
+        self.into_feed_items_by(&host_author, &host_topic)
+    }
+
+    // author and topic as default, the author in entry goes first
+    pub fn into_feed_items_by(&self, author: &str, topic: &str) -> Vec<NewItem> {
+        let mut feed_items: Vec<NewItem> = Vec::new();
+        for entry in parse_feed(&self.html) {
+            let link = abs_link(&self.url, entry.link.trim());
+            let item_author = if entry.author.trim().len() > 0 {
+                entry.author.trim().to_owned()
+            } else {
+                author.trim().to_owned()
+            };
+            feed_items.push(NewItem {
+                title: replace_space(entry.title.trim(), " "),
+                content: entry.summary.trim().to_owned(),
+                logo: String::new(),
+                author: item_author,
+                ty: "Article".to_owned(),
+                topic: topic.trim().to_owned(),
+                canonical_link: canonical_url(&link),
+                link,
+                post_by: "bot".to_owned(),
+                pub_at: entry.pub_at.unwrap_or(Utc::today().naive_utc()),
+                is_pending: false,
+            });
+        }
+
+        feed_items
+    }
+
     pub fn into_item(&self) -> NewItem {
+        let (host_author, host_topic) = get_author_topic(self.domain.trim());
+        self.into_item_by(&host_author, &host_topic)
+    }
+
+    // author and topic as default, the author found in page goes first
+    pub fn into_item_by(&self, author: &str, topic: &str) -> NewItem {
+        self.into_item_kw(author, topic).0
+    }
+
+    // the same, w/ the keywords of page, to classify
+    pub fn into_item_kw(&self, author: &str, topic: &str) -> (NewItem, Vec<String>) {
         let url = self.get_url();
         let html = self.get_html();
-        let domain = self.get_domain();
-        let dmn = domain.trim();
-        match dmn {
-            _ => {
-                let page = parse_common_page(html, &url);
-                let title = page.title.trim();
-                NewItem {
-                    title: replace_space(title, " "),
-                    content: page.content.trim().to_owned(),
-                    logo: page.img.trim().to_owned(),
-                    author: get_author_topic(dmn).0,
-                    ty: "Article".to_owned(),
-                    topic: get_author_topic(dmn).1,
-                    link: page.url.trim().to_owned(),
-                    post_by: "bot".to_owned(),
-                    pub_at: Utc::today().naive_utc()
-                }
-            }
-        }
+        let page = parse_common_page(html, &url);
+        let title = page.title.trim();
+        let item_author = if page.author.trim().len() > 0 {
+            page.author.trim().to_owned()
+        } else {
+            author.trim().to_owned()
+        };
+        let item = NewItem {
+            title: replace_space(title, " "),
+            content: page.content.trim().to_owned(),
+            logo: page.img.trim().to_owned(),
+            author: item_author,
+            ty: "Article".to_owned(),
+            topic: topic.trim().to_owned(),
+            link: page.url.trim().to_owned(),
+            post_by: "bot".to_owned(),
+            pub_at: page.pub_at,
+            is_pending: false,
+            canonical_link: canonical_url(&page.url),
+        };
+        (item, page.keywords)
     }
 }
 
@@ -160,15 +241,20 @@ pub fn page_ele_paser(
 }
 
 pub fn parse_common_page(html: Html, url: &str) -> PageInfo {
-    
-    // get title
-    let title_text: String = 
+    // JSON-LD if any
+    let ld = parse_ld_meta(&html);
+
+    // get title, the headline w/o site name suffix goes first
+    let title_text: String = if ld.headline.trim().len() > 0 {
+        ld.headline.trim().to_owned()
+    } else {
         page_ele_paser(
             &html, "head > title", "", url
         )
         .first()
         .unwrap_or(&String::from(url))
-        .to_string();
+        .to_string()
+    };
 
     // get image url
     //
@@ -180,7 +266,11 @@ pub fn parse_common_page(html: Html, url: &str) -> PageInfo {
         .unwrap_or(&String::from(""))
         .to_string();
     
-    let img_src: String = og_img;
+    let img_src: String = if og_img.len() == 0 {
+        ld.image.clone()
+    } else {
+        og_img
+    };
     // if og_img.len() == 0 {
     //     // random body img
     //     page_ele_paser(
@@ -203,7 +293,7 @@ pub fn parse_common_page(html: Html, url: &str) -> PageInfo {
     .unwrap_or(&String::from(""))
     .to_string();
 
-    let content: String = if meta_descript.len() == 0 {
+    let descript: String = if meta_descript.len() == 0 {
         // og:description
         page_ele_paser(
             &html, r#"meta[property="og:description"]"#, "content", ""
@@ -215,6 +305,14 @@ pub fn parse_common_page(html: Html, url: &str) -> PageInfo {
         meta_descript
     };
 
+    // the main content, as summary if no description
+    let main = extract_main(&html);
+    let content: String = if descript.trim().len() == 0 {
+        cut_text(&main.text, SUMMARY_LEN)
+    } else {
+        descript
+    };
+
     // get canonical link
     let c_link: String = page_ele_paser(
         &html, r#"link[rel="canonical"]"#, "href", url
@@ -222,18 +320,398 @@ pub fn parse_common_page(html: Html, url: &str) -> PageInfo {
     .first()
     .unwrap_or(&String::from(url))
     .to_string();
+
+    // get publish date, fallback to today
+    let pub_at = get_pub_date(&html, &ld, &c_link)
+        .or(get_pub_date(&html, &ld, url))
+        .unwrap_or(Utc::today().naive_utc());
+
+    // get author: JSON-LD, or meta author
+    let author: String = if ld.author.trim().len() > 0 {
+        ld.author.trim().to_owned()
+    } else {
+        page_ele_paser(
+            &html, r#"meta[name="author"]"#, "content", ""
+        )
+        .first()
+        .unwrap_or(&String::from(""))
+        .trim()
+        .to_string()
+    };
+
+    // get keywords: JSON-LD, or meta keywords
+    let keywords: Vec<String> = if ld.keywords.len() > 0 {
+        ld.keywords
+    } else {
+        let kws = page_ele_paser(
+            &html, r#"meta[name="keywords"]"#, "content", ""
+        );
+        split_keywords(kws.first().map(|k| k.as_str()).unwrap_or(""))
+    };
     
     PageInfo {
         title: title_text,
         url: c_link,
         img: img_src,
         content,
+        pub_at,
+        author,
+        keywords,
+        word_count: main.word_count,
     }
 }
 
+// the main content of page, readability-like
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct MainContent {
+    pub text: String,
+    pub word_count: usize,
+}
+
+lazy_static! {
+    static ref Unlikely_re: Regex = Regex::new(
+        r"(?i)comment|footer|footnote|nav|sidebar|menu|header|related|share|social|sponsor|promo|banner|popup|cookie|subscribe|widget"
+    ).unwrap();
+    static ref Positive_re: Regex = Regex::new(
+        r"(?i)article|body|content|entry|main|post|text|blog|story"
+    ).unwrap();
+    static ref Negative_re: Regex = Regex::new(
+        r"(?i)comment|footer|sidebar|widget|meta|related|share|promo|hidden|\bad"
+    ).unwrap();
+}
+
+// score the parents of paragraphs, pick the best as article body
+pub fn extract_main(html: &Html) -> MainContent {
+    use std::collections::HashMap;
+    let p_sel = match Selector::parse("p, pre, td") {
+        Ok(s) => s,
+        _ => return MainContent::default(),
+    };
+
+    let mut scores = HashMap::new();
+    for p in html.select(&p_sel) {
+        if is_unlikely(&p) { continue; }
+        let text = collapse_text(p.text());
+        let len = text.chars().count();
+        if len < 25 { continue; }
+        // more text, more commas, more likely the content
+        let score = 1.0 
+            + text.matches(',').count() as f64 
+            + (len as f64 / 100.0).min(3.0);
+
+        let parent = match p.parent().and_then(ElementRef::wrap) {
+            Some(pa) => pa,
+            None => continue,
+        };
+        *scores.entry(parent.id()).or_insert_with(|| init_score(&parent)) += score;
+        if let Some(gp) = parent.parent().and_then(ElementRef::wrap) {
+            *scores.entry(gp.id()).or_insert_with(|| init_score(&gp)) += score / 2.0;
+        }
+    }
+
+    // less links, more likely the content
+    let mut best = None;
+    let mut best_score = 0.0;
+    for (node_id, score) in scores.iter() {
+        let el = match html.tree.get(*node_id).and_then(ElementRef::wrap) {
+            Some(e) => e,
+            None => continue,
+        };
+        let final_score = score * (1.0 - link_density(&el));
+        if best.is_none() || final_score > best_score {
+            best = Some(el);
+            best_score = final_score;
+        }
+    }
+
+    let el = match best {
+        Some(e) => e,
+        None => return MainContent::default(),
+    };
+    let paras: Vec<String> = el
+        .select(&p_sel)
+        .map(|p| collapse_text(p.text()))
+        .filter(|t| t.len() > 0)
+        .collect();
+    let text = if paras.len() > 0 {
+        paras.join(" ")
+    } else {
+        collapse_text(el.text())
+    };
+    let word_count = text.split_whitespace().count();
+
+    MainContent { text, word_count }
+}
+
+fn class_id(el: &ElementRef) -> String {
+    let v = el.value();
+    v.attr("class").unwrap_or("").to_owned() + " " + v.attr("id").unwrap_or("")
+}
+
+// in nav, footer, comments...
+fn is_unlikely(el: &ElementRef) -> bool {
+    for node in el.ancestors() {
+        let anc = match ElementRef::wrap(node) {
+            Some(a) => a,
+            None => continue,
+        };
+        match anc.value().name() {
+            "nav" | "footer" | "aside" | "header" | "form" => return true,
+            "body" | "html" => return false,
+            _ => {}
+        }
+        let ci = class_id(&anc);
+        if Unlikely_re.is_match(&ci) && !Positive_re.is_match(&ci) {
+            return true;
+        }
+    }
+    false
+}
+
+fn init_score(el: &ElementRef) -> f64 {
+    let tag_score = match el.value().name() {
+        "article" => 10.0,
+        "main" | "div" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "ol" | "ul" | "dl" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    let ci = class_id(el);
+    let mut class_score = 0.0;
+    if Positive_re.is_match(&ci) { class_score += 25.0; }
+    if Negative_re.is_match(&ci) { class_score -= 25.0; }
+
+    tag_score + class_score
+}
+
+// the ratio of link text in all text
+fn link_density(el: &ElementRef) -> f64 {
+    let text_len = collapse_text(el.text()).chars().count();
+    if text_len == 0 {
+        return 1.0;
+    }
+    let a_sel = match Selector::parse("a") {
+        Ok(s) => s,
+        _ => return 0.0,
+    };
+    let link_len: usize = el
+        .select(&a_sel)
+        .map(|a| collapse_text(a.text()).chars().count())
+        .sum();
+    link_len as f64 / text_len as f64
+}
+
+fn collapse_text<'a, I: Iterator<Item = &'a str>>(texts: I) -> String {
+    let joined: Vec<&str> = texts.collect();
+    joined.join(" ").split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// try in order: meta, JSON-LD, <time>, then the date in url
+pub fn get_pub_date(html: &Html, ld: &LdMeta, url: &str) -> Option<NaiveDate> {
+    let meta_sels = vec!(
+        r#"meta[property="article:published_time"]"#,
+        r#"meta[name="article:published_time"]"#,
+        r#"meta[itemprop="datePublished"]"#,
+        r#"meta[name="date"]"#,
+    );
+    for sel in meta_sels {
+        for d in page_ele_paser(html, sel, "content", "") {
+            if let Some(date) = check_date(parse_feed_date(&d)) {
+                return Some(date);
+            }
+        }
+    }
+
+    if ld.date_published.is_some() {
+        return ld.date_published;
+    }
+
+    for d in page_ele_paser(html, "time[datetime]", "datetime", "") {
+        if let Some(date) = check_date(parse_feed_date(&d)) {
+            return Some(date);
+        }
+    }
+
+    check_date(date_in_url(url))
+}
+
+// a date in future or too old is not trusted
+fn check_date(date: Option<NaiveDate>) -> Option<NaiveDate> {
+    let today = Utc::today().naive_utc();
+    date.filter(|d| *d <= today && *d >= NaiveDate::from_ymd(1990, 1, 1))
+}
+
+// url like: /2020/10/19/slug, /2020-10-19-slug, /2020/10/slug
+pub fn date_in_url(url: &str) -> Option<NaiveDate> {
+    lazy_static! {
+        static ref Ymd_re: Regex = 
+            Regex::new(r"/(\d{4})[/-](\d{1,2})[/-](\d{1,2})(/|-|\.|$)").unwrap();
+        static ref Ym_re: Regex = 
+            Regex::new(r"/(\d{4})/(\d{1,2})/").unwrap();
+    }
+    let num = |c: &regex::Captures, i: usize| -> u32 {
+        c.get(i).map(|m| m.as_str()).unwrap_or("0").parse().unwrap_or(0)
+    };
+    if let Some(c) = Ymd_re.captures(url) {
+        return NaiveDate::from_ymd_opt(num(&c, 1) as i32, num(&c, 2), num(&c, 3));
+    }
+    if let Some(c) = Ym_re.captures(url) {
+        return NaiveDate::from_ymd_opt(num(&c, 1) as i32, num(&c, 2), 1);
+    }
+
+    None
+}
+
+// all JSON-LD objects in page
+pub fn ld_json_values(html: &Html) -> Vec<serde_json::Value> {
+    let mut values = Vec::new();
+    let scripts = page_ele_paser(
+        html, r#"script[type="application/ld+json"]"#, "", ""
+    );
+    for s in scripts {
+        if let Ok(v) = serde_json::from_str::<serde_json::Value>(s.trim()) {
+            values.push(v);
+        }
+    }
+
+    values
+}
+
+// the Article / BlogPosting object in JSON-LD
+pub fn parse_ld_meta(html: &Html) -> LdMeta {
+    use serde_json::Value;
+
+    // flatten top level arrays and @graph
+    let mut nodes: Vec<Value> = Vec::new();
+    for v in ld_json_values(html) {
+        let list = match v {
+            Value::Array(arr) => arr,
+            other => vec!(other),
+        };
+        for n in list {
+            let graph = n.get("@graph").and_then(|g| g.as_array()).cloned();
+            match graph {
+                Some(g) => nodes.extend(g),
+                None => nodes.push(n),
+            }
+        }
+    }
+
+    let article = match nodes.iter().find(|n| is_ld_article(n)) {
+        Some(a) => a,
+        None => return LdMeta::default(),
+    };
+
+    let author = article
+        .get("author")
+        .map(|a| ld_names(a, &nodes))
+        .unwrap_or_default();
+    let image = article
+        .get("image")
+        .map(|i| ld_url(i))
+        .unwrap_or_default();
+    let keywords = match article.get("keywords") {
+        Some(Value::String(s)) => split_keywords(s),
+        Some(Value::Array(arr)) => arr
+            .iter()
+            .filter_map(|k| k.as_str())
+            .map(|k| k.trim().to_owned())
+            .filter(|k| k.len() > 0)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let date_published = article
+        .get("datePublished")
+        .and_then(|d| d.as_str())
+        .and_then(|d| check_date(parse_feed_date(d)));
+
+    LdMeta {
+        headline: article
+            .get("headline")
+            .and_then(|h| h.as_str())
+            .map(|h| replace_space(h.trim(), " "))
+            .unwrap_or_default(),
+        author,
+        image,
+        keywords,
+        date_published,
+    }
+}
+
+// @type: Article, BlogPosting, NewsArticle, TechArticle...; can be an array
+fn is_ld_article(v: &serde_json::Value) -> bool {
+    let check = |t: &str| t.ends_with("Article") || t.ends_with("Posting");
+    match v.get("@type") {
+        Some(serde_json::Value::String(t)) => check(t),
+        Some(serde_json::Value::Array(ts)) => {
+            ts.iter().filter_map(|t| t.as_str()).any(check)
+        }
+        _ => false,
+    }
+}
+
+// author: "name", {"name"}, [{"name"}], or {"@id"} refer to a node in graph
+fn ld_names(v: &serde_json::Value, nodes: &[serde_json::Value]) -> String {
+    use serde_json::Value;
+    match v {
+        Value::String(s) => s.trim().to_owned(),
+        Value::Object(map) => {
+            if let Some(name) = map.get("name").and_then(|n| n.as_str()) {
+                return name.trim().to_owned();
+            }
+            let ref_id = map.get("@id").and_then(|i| i.as_str()).unwrap_or("");
+            nodes
+                .iter()
+                .find(|n| {
+                    ref_id.len() > 0
+                        && n.get("@id").and_then(|i| i.as_str()) == Some(ref_id)
+                })
+                .and_then(|n| n.get("name"))
+                .and_then(|n| n.as_str())
+                .unwrap_or("")
+                .trim()
+                .to_owned()
+        }
+        Value::Array(arr) => {
+            let names: Vec<String> = arr
+                .iter()
+                .map(|a| ld_names(a, nodes))
+                .filter(|a| a.len() > 0)
+                .collect();
+            names.join(", ")
+        }
+        _ => String::new(),
+    }
+}
+
+// image: "url", {"url"}, or an array of them
+fn ld_url(v: &serde_json::Value) -> String {
+    use serde_json::Value;
+    match v {
+        Value::String(s) => s.trim().to_owned(),
+        Value::Object(map) => map
+            .get("url")
+            .and_then(|u| u.as_str())
+            .unwrap_or("")
+            .trim()
+            .to_owned(),
+        Value::Array(arr) => arr.first().map(|i| ld_url(i)).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+// "rust, web,async" -> ["rust", "web", "async"]
+fn split_keywords(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(|k| k.trim().to_owned())
+        .filter(|k| k.len() > 0)
+        .collect()
+}
+
 // some helpers
 
-fn get_author_topic(host: &str) -> (String, String) {
+pub fn get_author_topic(host: &str) -> (String, String) {
     let map = &MAP_HOST;
     let default = &(host, "Rust");
     let tup = map.get(host).unwrap_or(default);
@@ -241,6 +719,18 @@ fn get_author_topic(host: &str) -> (String, String) {
     (tup.0.to_owned(), tup.1.to_owned())
 }
 
+// make relative link absolute per the page url
+pub fn abs_link(base_url: &str, link: &str) -> String {
+    if link.starts_with("http://") || link.starts_with("https://") {
+        return link.to_owned();
+    }
+    // relative to the path of base, as the browser
+    reqwest::Url::parse(base_url.trim())
+        .and_then(|base| base.join(link))
+        .map(|u| u.to_string())
+        .unwrap_or(link.to_owned())
+}
+
 // replace whitespance, \n \t \r \f...
 pub fn replace_space(text: &str, rep: &str) -> String {
     lazy_static! {
@@ -249,3 +739,117 @@ pub fn replace_space(text: &str, rep: &str) -> String {
     }
     RE.replace_all(text, rep).into_owned()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abs_link_of_page() {
+        let base = "https://a.com/blog/";
+        assert_eq!(abs_link(base, "post.html"), "https://a.com/blog/post.html");
+        assert_eq!(abs_link(base, "./post.html"), "https://a.com/blog/post.html");
+        assert_eq!(abs_link(base, "../about"), "https://a.com/about");
+        assert_eq!(abs_link(base, "/2020/post"), "https://a.com/2020/post");
+        assert_eq!(abs_link(base, "//b.com/x"), "https://b.com/x");
+        assert_eq!(abs_link("https://a.com/blog/index.html", "post.html"), "https://a.com/blog/post.html");
+        assert_eq!(abs_link("http://a.com", "p"), "http://a.com/p");
+        assert_eq!(abs_link(base, "https://c.com/y"), "https://c.com/y");
+    }
+
+    #[test]
+    fn date_from_url() {
+        assert_eq!(
+            date_in_url("https://a.com/2019/05/12/some-post/"),
+            Some(NaiveDate::from_ymd(2019, 5, 12))
+        );
+        assert_eq!(
+            date_in_url("https://a.com/posts/2020-01-02-some-post"),
+            Some(NaiveDate::from_ymd(2020, 1, 2))
+        );
+        assert_eq!(
+            date_in_url("https://a.com/2018/11/some-post.html"),
+            Some(NaiveDate::from_ymd(2018, 11, 1))
+        );
+        assert_eq!(date_in_url("https://a.com/blog/some-post"), None);
+    }
+
+    #[test]
+    fn date_from_page() {
+        let meta = Html::parse_document(
+            r#"<html><head><meta property="article:published_time" 
+            content="2019-03-04T10:00:00+00:00"></head><body></body></html>"#
+        );
+        assert_eq!(
+            get_pub_date(&meta, &parse_ld_meta(&meta), "https://a.com/2020/01/01/x"),
+            Some(NaiveDate::from_ymd(2019, 3, 4))
+        );
+
+        let ld = Html::parse_document(
+            r#"<html><head><script type="application/ld+json">
+            {"@context":"https://schema.org","@graph":[
+              {"@type":"BlogPosting","datePublished":"2018-07-08"}
+            ]}</script></head><body></body></html>"#
+        );
+        assert_eq!(
+            get_pub_date(&ld, &parse_ld_meta(&ld), ""),
+            Some(NaiveDate::from_ymd(2018, 7, 8))
+        );
+
+        let time = Html::parse_document(
+            r#"<html><body><time datetime="2017-01-02">Jan 2</time></body></html>"#
+        );
+        assert_eq!(
+            get_pub_date(&time, &LdMeta::default(), ""),
+            Some(NaiveDate::from_ymd(2017, 1, 2))
+        );
+    }
+
+    #[test]
+    fn ld_meta_from_page() {
+        let html = Html::parse_document(
+            r#"<html><head><script type="application/ld+json">
+            {"@context":"https://schema.org","@graph":[
+              {"@type":"WebSite","@id":"https://a.com/#site","name":"A Blog"},
+              {"@type":"Person","@id":"https://a.com/#me","name":"Jane Doe"},
+              {"@type":["BlogPosting"],"headline":"Hello  World",
+               "author":{"@id":"https://a.com/#me"},
+               "image":{"@type":"ImageObject","url":"https://a.com/a.png"},
+               "keywords":"rust, async ,web","datePublished":"2020-09-10"}
+            ]}</script></head><body></body></html>"#
+        );
+        let ld = parse_ld_meta(&html);
+        assert_eq!(ld.headline, "Hello World");
+        assert_eq!(ld.author, "Jane Doe");
+        assert_eq!(ld.image, "https://a.com/a.png");
+        assert_eq!(ld.keywords, vec!("rust", "async", "web"));
+        assert_eq!(ld.date_published, Some(NaiveDate::from_ymd(2020, 9, 10)));
+
+        let page = parse_common_page(html, "https://a.com/hello");
+        assert_eq!(page.title, "Hello World");
+        assert_eq!(page.author, "Jane Doe");
+        assert_eq!(page.keywords, vec!("rust", "async", "web"));
+    }
+
+    #[test]
+    fn main_content_of_page() {
+        let html = Html::parse_document(
+            r#"<html><body>
+            <nav><p>Home, Blog, About, Archive, Tags, Feeds and more links here</p></nav>
+            <div class="post-content">
+              <h1>Title</h1>
+              <p>Rust makes it easy to write fast, reliable software, and this post shows how.</p>
+              <p>We start with ownership, then borrowing, and finally lifetimes in detail.</p>
+            </div>
+            <div class="comments"><p>Great post, thanks a lot for sharing this with us all!</p></div>
+            </body></html>"#
+        );
+        let main = extract_main(&html);
+        assert!(main.text.starts_with("Rust makes it easy"));
+        assert!(main.text.ends_with("lifetimes in detail."));
+        assert_eq!(main.word_count, 25);
+
+        let page = parse_common_page(html, "https://a.com/post");
+        assert!(page.content.starts_with("Rust makes it easy"));
+    }
+}
@@ -23,78 +23,308 @@ pub fn spider_items(env: &Environment) -> Result<(), PerformError> {
 
 pub fn spider_and_save_item(conn: &PgConnection) -> QueryResult<()> {
     use crate::schema::items::dsl::*;
-    use crate::bot::spider::{WebPage};
-
-    // new WebPages and get all links
-    let mut links: Vec<String> = Vec::new();
-    use crate::bot::cfg::LINK_VEC;
-    let url_list = &LINK_VEC;
-    for url in url_list.iter() {
-        // println!("{}", url);
-        let page = WebPage::new(url).unwrap_or_default();
-        links.append(&mut page.clean_links());
+    use crate::bot::crawler::{CRAWLER, CondReq};
+
+    // new WebPages per the enabled sources in db and get all links, 
+    // or get items from feed directly if the source is a feed
+    use crate::api::source::Source;
+    use crate::bot::cfg::get_source_links;
+    let src_list = Source::load_enabled(conn)?;
+    // conditional GET, w/ the validators of last run
+    let src_reqs: Vec<CondReq> = src_list
+        .iter()
+        .map(|s| CondReq {
+            url: s.url.clone(),
+            etag: s.etag.clone(),
+            last_modified: s.last_modified.clone(),
+        })
+        .collect();
+    let src_pages = CRAWLER.crawl_cond(src_reqs);
+    // type of items, and topic if the source has none
+    use crate::bot::classify::Classifier;
+    let classifier = Classifier::load(conn)?;
+    let mut links: Vec<(String, usize)> = Vec::new();  // (link, index of source)
+    let mut feed_items: Vec<NewItem> = Vec::new();
+    // log each fetch of this run
+    use crate::api::crawl::{CrawlLog, NewCrawlLog, LOG_KEEP_DAYS};
+    let run_at = chrono::Utc::now().naive_utc();
+    let mut crawl_logs: Vec<NewCrawlLog> = Vec::new();
+    for (idx, (src, page)) in src_list.iter().zip(src_pages.iter()).enumerate() {
+        // println!("{}", src.url);
+        // not modified since last run, or failed
+        if !page.is_ok() { 
+            crawl_logs.push(NewCrawlLog::from_page(page, run_at, "source", Some(src.id), 0));
+            continue; 
+        }
+        let found = if page.is_feed() {
+            let (src_author, src_topic) = src.author_topic(page.domain.trim());
+            let mut fis = page.into_feed_items_by(&src_author, &src_topic);
+            for fi in fis.iter_mut() {
+                classifier.apply(fi, &[], src.has_topic());
+            }
+            let n = fis.len();
+            feed_items.extend(fis);
+            n
+        } else {
+            let ls = get_source_links(page, src);
+            let n = ls.len();
+            for l in ls {
+                links.push((l, idx));
+            }
+            n
+        };
+        crawl_logs.push(NewCrawlLog::from_page(page, run_at, "source", Some(src.id), found));
     }
     // println!("{:?}", links);
     
     // diff the links w/ db
     //
-    // extracted new links
-    use std::collections::HashSet;
-    let mut links_set = HashSet::new();
-    for l in links {
+    // extracted new links, w/ the source it from,
+    // keyed by the canonical url to dedup
+    use std::collections::{HashMap, HashSet};
+    use crate::util::helper::canonical_url;
+    let mut links_map = HashMap::new();
+    for (l, idx) in links {
         // regex check url
         use crate::api::re_test_url;
         if re_test_url(&l) {
-            links_set.insert(l);
+            links_map.insert(canonical_url(&l), (l, idx));
         }
     }
 
-    // deserde spidered links from Json as reduce query db
+    // diff w/ the urls seen by bot
     //
-    use crate::util::helper::{deserde_links, serde_links, serde_add_links};
-    let sped_links = deserde_links();
-    let sp_links = if sped_links.len() > 0 {
-        sped_links
-    } else {
-        let db_links: Vec<String> = items
-            .filter(link.ne(""))
-            //.filter(post_at.lt(limit_day))  // did nothing
-            .select(link)
-            .load::<String>(conn)?;
-        serde_links(db_links.clone());
-        db_links
-    };
-    let mut spd_links_set = HashSet::new();
-    for l in sp_links {
-        spd_links_set.insert(l);
-    }
+    use crate::bot::seen::{filter_unseen, add_urls, touch_urls};
+    let mut check_links: Vec<String> = links_map.keys().cloned().collect();
+    check_links.extend(feed_items.iter().map(|fi| fi.canonical_link.clone()));
+    let unseen_set: HashSet<String> = filter_unseen(conn, check_links.clone())?
+        .into_iter()
+        .collect();
+    let seen_links: Vec<String> = check_links
+        .into_iter()
+        .filter(|l| !unseen_set.contains(l))
+        .collect();
+    touch_urls(conn, &seen_links)?;
 
-    // diff the real new links to feed spider
-    let diff_links = links_set.difference(&spd_links_set);
-    //println!("{:#?}", diff_links);
     let mut new_links: Vec<String> = Vec::new();
-    // spider the diff_links and build item
     let mut new_items: Vec<NewItem> = Vec::new();
-    for l in diff_links {
-        let sp_item = WebPage::new(l)
-            .unwrap_or_default()
-            .into_item();
-        new_items.push(sp_item);
-        new_links.push(l.to_string());
+
+    // items from feed, keep the new ones only
+    use crate::api::re_test_url;
+    for fi in feed_items {
+        if re_test_url(&fi.link) 
+            && unseen_set.contains(&fi.canonical_link) 
+            && !new_links.contains(&fi.canonical_link) 
+        {
+            new_links.push(fi.canonical_link.clone());
+            new_items.push(fi);
+        }
     }
 
-    // save new items to db
-    diesel::insert_into(items)
+    // diff the real new links to feed spider
+    // spider the diff_links concurrently and build item
+    let mut diff_links: Vec<(String, String, usize)> = Vec::new();
+    for (c_link, (l, idx)) in links_map.into_iter() {
+        if !unseen_set.contains(&c_link) || new_links.contains(&c_link) { continue; }
+        diff_links.push((c_link, l, idx));
+    }
+    let diff_urls: Vec<String> = diff_links.iter().map(|(_, l, _)| l.clone()).collect();
+    let diff_pages = CRAWLER.crawl(diff_urls);
+    let mut sp_items: Vec<NewItem> = Vec::new();
+    // the sources w/ any link failed, to fetch in full next run
+    let mut failed_srcs: HashSet<usize> = HashSet::new();
+    for ((c_link, _, idx), page) in diff_links.into_iter().zip(diff_pages.iter()) {
+        crawl_logs.push(
+            NewCrawlLog::from_page(page, run_at, "page", Some(src_list[idx].id), 0)
+        );
+        // skip the failed, try again next time
+        if !page.is_ok() || page.html.trim().len() == 0 {
+            failed_srcs.insert(idx);
+            continue;
+        }
+        new_links.push(c_link);
+        // the author found in page goes first, then the source's
+        let src = &src_list[idx];
+        let (src_author, src_topic) = src.author_topic(page.domain.trim());
+        let (mut sp_item, keywords) = page.into_item_kw(&src_author, &src_topic);
+        classifier.apply(&mut sp_item, &keywords, src.has_topic());
+        sp_items.push(sp_item);
+    }
+    // the canonical link in page may be a seen one
+    let sp_links: Vec<String> = sp_items.iter().map(|i| i.canonical_link.clone()).collect();
+    let sp_unseen = filter_unseen(conn, sp_links)?;
+    for sp_item in sp_items {
+        if sp_unseen.contains(&sp_item.canonical_link) 
+            && !new_items.iter().any(|i| i.canonical_link == sp_item.canonical_link) 
+        {
+            new_links.push(sp_item.canonical_link.clone());
+            new_items.push(sp_item);
+        }
+    }
+
+    // local thumbnails, not hotlink; wait for review by admin
+    use crate::util::thumb::cache_thumb;
+    for ni in new_items.iter_mut() {
+        ni.logo = cache_thumb(&ni.logo);
+        ni.is_pending = true;
+    }
+
+    // save new items to db, and flag the near-duplicates
+    let saved_items = diesel::insert_into(items)
         .values(&new_items)
         .on_conflict_do_nothing()
-        .execute(conn)?;
+        .get_results::<Item>(conn)?;
+    Item::mark_dups(saved_items, conn)?;
     
-    // save new links to json
-    serde_add_links(new_links);
+    // save new links as seen
+    add_urls(conn, &new_links)?;
+
+    // save crawl logs, and drop the old
+    NewCrawlLog::save_all(&crawl_logs, conn)?;
+    CrawlLog::prune(conn, LOG_KEEP_DAYS)?;
+
+    // keep validators after all saved, else the new links missed next run;
+    // not if any link failed, a 304 next run would hide it for good
+    for (idx, (src, page)) in src_list.iter().zip(src_pages.iter()).enumerate() {
+        if !page.is_ok() || failed_srcs.contains(&idx) { continue; }
+        src.save_validators(conn, &page.etag, &page.last_modified)?;
+    }
+
+    Ok(())
+}
+
+
+// link rot
+//
+// check the links of items, flag the dead and follow the moved
+#[swirl::background_job]
+pub fn check_item_links(env: &Environment) -> Result<(), PerformError> {
+    let conn = env.connection()?;
+    check_links_and_save(&conn)?;
 
     Ok(())
 }
 
+pub fn check_links_and_save(conn: &PgConnection) -> QueryResult<()> {
+    use crate::schema::items::dsl::{items, id, link, canonical_link, is_dead};
+    use crate::util::helper::canonical_url;
+    use crate::schema::link_checks::dsl::{link_checks, item_id, check_at};
+    use crate::bot::crawler::CRAWLER;
+    use crate::bot::linkcheck::{
+        LinkCheck, Verdict, CHECK_BATCH, CHECK_EVERY_DAYS, DEAD_FAILS,
+    };
+    use std::collections::HashMap;
+
+    // the links not checked recently
+    let now = chrono::Utc::now().naive_utc();
+    let before = now - chrono::Duration::days(CHECK_EVERY_DAYS);
+    let fresh = link_checks.filter(check_at.gt(before)).select(item_id);
+    let to_check: Vec<(i32, String, bool)> = items
+        .filter(id.ne_all(fresh))
+        .order(id.asc())
+        .limit(CHECK_BATCH)
+        .select((id, link, is_dead))
+        .load::<(i32, String, bool)>(conn)?;
+    if to_check.len() == 0 {
+        return Ok(());
+    }
+
+    let ids: Vec<i32> = to_check.iter().map(|(i, _, _)| *i).collect();
+    let last_fails: HashMap<i32, i32> = link_checks
+        .filter(item_id.eq(any(&ids)))
+        .load::<LinkCheck>(conn)?
+        .into_iter()
+        .map(|c| (c.item_id, c.fails))
+        .collect();
+
+    let urls: Vec<String> = to_check.iter().map(|(_, l, _)| l.clone()).collect();
+    let results = CRAWLER.check_links(urls);
+
+    use crate::bot::seen::add_urls;
+    for ((iid, ilink, dead), res) in to_check.into_iter().zip(results.iter()) {
+        let prev = last_fails.get(&iid).cloned().unwrap_or(0);
+        let fails = match res.verdict() {
+            Verdict::Alive => 0,
+            Verdict::Moved(new_link) => {
+                // keep the link if the new one is another item's
+                let c_link = canonical_url(&new_link);
+                let taken = items
+                    .filter(canonical_link.eq(&c_link))
+                    .filter(id.ne(iid))
+                    .select(id)
+                    .first::<i32>(conn)
+                    .optional()?
+                    .is_some();
+                if !taken && new_link != ilink {
+                    // as an edit by bot, can be seen and undone in history
+                    use crate::api::revision::ItemRevision;
+                    use crate::api::item::del_listed_html;
+                    let old = items.find(iid).first::<Item>(conn)?;
+                    let moved = conn.transaction::<Item, diesel::result::Error, _>(|| {
+                        let itm = diesel::update(&old)
+                            .set((link.eq(&new_link), canonical_link.eq(&c_link)))
+                            .get_result::<Item>(conn)?;
+                        ItemRevision::record(conn, &old, &itm, "bot")?;
+                        Ok(itm)
+                    })?;
+                    del_listed_html(&moved);
+                    add_urls(conn, &[c_link])?;
+                }
+                0
+            }
+            Verdict::Dead => prev + 1,
+            Verdict::Unknown => prev,
+        };
+        let now_dead = fails >= DEAD_FAILS;
+        if now_dead != dead {
+            diesel::update(items.find(iid))
+                .set(is_dead.eq(now_dead))
+                .execute(conn)?;
+        }
+        LinkCheck::new(iid, res, fails, now).save(conn)?;
+    }
+
+    Ok(())
+}
+
+
+// thumbnail
+//
+// cache the logo of item as a local thumbnail, not in the request
+#[swirl::background_job]
+pub fn cache_item_thumb(env: &Environment, item_id: i32) -> Result<(), PerformError> {
+    let conn = env.connection()?;
+    save_item_thumb(&conn, item_id)?;
+
+    Ok(())
+}
+
+pub fn save_item_thumb(conn: &PgConnection, item_id: i32) -> QueryResult<()> {
+    use crate::schema::items::dsl::{items, logo};
+    use crate::util::thumb::{cache_thumb, is_local};
+    let remote = items.find(item_id).select(logo).get_result::<String>(conn)?;
+    if remote.trim().len() == 0 || is_local(&remote) {
+        return Ok(());
+    }
+    let local = cache_thumb(&remote);
+    if local != remote {
+        // not overwrite a logo changed meanwhile
+        diesel::update(items.find(item_id).filter(logo.eq(&remote)))
+            .set(logo.eq(&local))
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+// enqueue, ignore potential error, the remote logo kept then
+pub fn queue_item_thumb(conn: &PgConnection, item_id: i32) {
+    use swirl::Job;
+    if let Err(e) = cache_item_thumb(item_id).enqueue(conn) {
+        log::error!("enqueue thumb of item {}: {}", item_id, e);
+    }
+}
+
 
 // Cal 
 //
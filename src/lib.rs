@@ -137,10 +137,37 @@ pub async fn init_server() -> std::io::Result<()> {
                         .route(delete().to(api::blog::del))
                 )
                 .service(
-                    resource("/items")
+                    resource("/items")  // get: ?topic=&ty=&author=&since=&sort=..
+                        .route(get().to(api::item::get_items))
                         .route(post().to(api::item::new))
                         .route(put().to(api::item::update))
                 )
+                .service(
+                    resource("/sources")
+                        .route(post().to(api::source::new))
+                        .route(put().to(api::source::update))
+                        .route(get().to(api::source::get_list))
+                )
+                .service(
+                    resource("/sources/{id}")
+                        .route(get().to(api::source::get))
+                        .route(put().to(api::source::toggle_enabled))
+                        .route(delete().to(api::source::del))
+                )
+                .service(
+                    resource("/classify-rules")
+                        .route(post().to(api::classify::new))
+                        .route(get().to(api::classify::get_list))
+                )
+                .service(
+                    resource("/classify-rules/{id}")
+                        .route(delete().to(api::classify::del))
+                )
+                .service(
+                    resource("/crawl-report")
+                        // the failing sources: ?runs=3
+                        .route(get().to(api::crawl::report))
+                )
                 .service(
                     resource("/spider")
                         .route(put().to(api::item::spider))
@@ -154,10 +181,54 @@ pub async fn init_server() -> std::io::Result<()> {
                     resource("/items/{id}")
                         .route(get().to(api::item::get))
                         .route(patch().to(api::item::toggle_top))
-                        // vote or veto: ?action=vote|veto
+                        // vote or veto: ?action=vote|veto|clear
                         .route(put().to(api::item::vote_or_veto))
                         .route(delete().to(api::item::del))
                 )
+                .service(
+                    resource("/items/{id}/click")
+                        .route(post().to(api::item::click))
+                )
+                .service(
+                    resource("/items/{id}/revisions")
+                        .route(get().to(api::revision::get_list))
+                )
+                .service(
+                    resource("/items/{id}/revisions/{rid}")
+                        // revert to before the revision
+                        .route(put().to(api::revision::revert))
+                )
+                .service(
+                    resource("/search")  // query: ?q=&topic=&ty=&page=
+                        .route(get().to(api::search::search))
+                )
+                .service(
+                    resource("/bookmarks")
+                        // get_list: ?read=true|false&perpage=42&page=1
+                        .route(get().to(api::bookmark::get_list))
+                        .route(post().to(api::bookmark::new))
+                )
+                .service(
+                    resource("/bookmarks/{item_id}")
+                        // {note?, is_read?}
+                        .route(put().to(api::bookmark::update))
+                        .route(delete().to(api::bookmark::del))
+                )
+                .service(
+                    resource("/dups")
+                        .route(get().to(api::item::get_dups))
+                )
+                .service(
+                    resource("/dups/{id}")
+                        // ?action=merge|keep
+                        .route(put().to(api::item::handle_dup))
+                )
+                .service(
+                    resource("/pending")
+                        .route(get().to(api::item::get_pending))
+                        // bulk: {action: approve|reject|edit, ids}
+                        .route(put().to(api::item::moderate))
+                )
                 .service(
                     resource("/generate-sitemap")
                         .route(get().to(view::tmpl::gen_sitemap))
@@ -172,6 +243,11 @@ pub async fn init_server() -> std::io::Result<()> {
                 // )
                 .default_service(route().to(|| HttpResponse::NotFound()))
             )
+            .service(
+                // the thumbnails of logo, wherever THUMB_DIR is
+                fs::Files::new(util::thumb::THUMB_URL, util::thumb::THUMB_DIR.clone())
+                    .default_handler(route().to(|| HttpResponse::NotFound()))
+            )
             .service(
                 fs::Files::new("/static", "./static/") // static files
                     .default_handler(route().to(|| HttpResponse::NotFound()))
@@ -204,10 +280,22 @@ pub async fn init_server() -> std::io::Result<()> {
                 resource("/item/{id}")
                     .route(get().to(view::tmpl::item_view_either))
             )
+            .service( 
+                resource("/item/{id}/history")
+                    .route(get().to(view::tmpl::item_history))
+            )
             .service( 
                 resource("/item/{id}/dyn")
                     .route(get().to(view::tmpl::item_view_dyn))
             )
+            .service(
+                resource("/search")  // query: ?q=&topic=&ty=&page=
+                    .route(get().to(view::tmpl::search))
+            )
+            .service(
+                resource("/readlist")  // query: ?read=true|false&page=
+                    .route(get().to(view::tmpl::readlist))
+            )
             .service(
                 resource("/@{uname}")
                     .route(get().to(view::tmpl::profile))
@@ -224,6 +312,10 @@ pub async fn init_server() -> std::io::Result<()> {
                 resource("/newitem")
                     .route(get().to(view::form::new_item))
             )
+            .service(
+                resource("/moderation")
+                    .route(get().to(view::tmpl::moderation))
+            )
             .service(
                 resource("/edititem")  // query: ?id=
                     .route(get().to(view::form::edit_item))
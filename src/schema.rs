@@ -25,6 +25,34 @@ table! {
     }
 }
 
+table! {
+    bookmarks (uname, item_id) {
+        uname -> Varchar,
+        item_id -> Int4,
+        note -> Varchar,
+        is_read -> Bool,
+        save_at -> Timestamp,
+    }
+}
+
+table! {
+    clickitems (uname, item_id) {
+        uname -> Varchar,
+        item_id -> Int4,
+        click_at -> Timestamp,
+    }
+}
+
+table! {
+    classify_rules (id) {
+        id -> Int4,
+        kw -> Varchar,
+        kind -> Varchar,
+        target -> Varchar,
+        weight -> Int4,
+    }
+}
+
 table! {
     comments (id) {
         id -> Int4,
@@ -38,6 +66,40 @@ table! {
     }
 }
 
+table! {
+    crawl_logs (id) {
+        id -> Int4,
+        run_at -> Timestamp,
+        kind -> Varchar,
+        url -> Varchar,
+        source_id -> Nullable<Int4>,
+        status -> Int4,
+        duration_ms -> Int4,
+        error -> Varchar,
+        links_found -> Int4,
+        crawl_at -> Timestamp,
+    }
+}
+
+table! {
+    crawled_urls (url) {
+        url -> Varchar,
+        first_seen -> Timestamp,
+        last_checked -> Timestamp,
+        is_rejected -> Bool,
+    }
+}
+
+table! {
+    item_revisions (id) {
+        id -> Int4,
+        item_id -> Int4,
+        edit_by -> Varchar,
+        edit_at -> Timestamp,
+        diff -> Jsonb,
+    }
+}
+
 table! {
     itemcomments (item_id, comment_id) {
         item_id -> Int4,
@@ -69,6 +131,12 @@ table! {
         post_at -> Timestamp,
         is_top -> Bool,
         vote -> Int4,
+        simhash -> Int8,
+        dup_of -> Nullable<Int4>,
+        is_dead -> Bool,
+        is_pending -> Bool,
+        clicks -> Int4,
+        canonical_link -> Varchar,
     }
 }
 
@@ -83,6 +151,35 @@ table! {
     }
 }
 
+table! {
+    link_checks (item_id) {
+        item_id -> Int4,
+        url -> Varchar,
+        status -> Int4,
+        final_url -> Varchar,
+        permanent -> Bool,
+        error -> Varchar,
+        fails -> Int4,
+        check_at -> Timestamp,
+    }
+}
+
+table! {
+    sources (id) {
+        id -> Int4,
+        url -> Varchar,
+        author -> Varchar,
+        topic -> Varchar,
+        link_prefix -> Varchar,
+        link_regex -> Varchar,
+        link_selector -> Varchar,
+        is_enabled -> Bool,
+        add_at -> Timestamp,
+        etag -> Varchar,
+        last_modified -> Varchar,
+    }
+}
+
 table! {
     users (id) {
         id -> Int4,
@@ -124,20 +221,33 @@ table! {
     }
 }
 
+joinable!(bookmarks -> items (item_id));
+joinable!(clickitems -> items (item_id));
+joinable!(crawl_logs -> sources (source_id));
+joinable!(item_revisions -> items (item_id));
 joinable!(itemcomments -> comments (comment_id));
 joinable!(itemcomments -> items (item_id));
 joinable!(itemlabels -> items (item_id));
+joinable!(link_checks -> items (item_id));
 joinable!(votecomments -> comments (comment_id));
 joinable!(voteitems -> items (item_id));
 
 allow_tables_to_appear_in_same_query!(
     background_jobs,
     blogs,
+    bookmarks,
+    clickitems,
+    classify_rules,
     comments,
+    crawl_logs,
+    crawled_urls,
+    item_revisions,
     itemcomments,
     itemlabels,
     items,
     labels,
+    link_checks,
+    sources,
     users,
     votecomments,
     voteitems,
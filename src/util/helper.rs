@@ -96,45 +96,70 @@ pub fn get_host(s: &str) -> String {
     host
 }
 
-
-// serde links vec, save spidered links as json
+// canonical form of url, to dedup the same page:
 //
-#[derive(Serialize, Deserialize, Default, Debug)]
-pub struct SpLinks { pub links: Vec<String> }
-pub const LINKS_JSON_DIR: &str = "links.json";
-
-pub fn serde_links(links: Vec<String>) {
-    let sp_links = SpLinks { links: links };
-    let ser_sp_links = serde_json::to_string(&sp_links)
-        .unwrap_or(String::new());
-    std::fs::write(LINKS_JSON_DIR, ser_sp_links.as_bytes()).unwrap_or(());
+// https, no www, no default port, no fragment, no tracking params,
+// no /ref=..., no trailing slash; a key to compare, not a link to save
+pub fn canonical_url(url: &str) -> String {
+    lazy_static! {
+        static ref RE_URL: Regex =
+            Regex::new(r"(?i)^https?://([^/?#]+)([^?#]*)(\?[^#]*)?").unwrap();
+        static ref RE_REF: Regex =
+            Regex::new(r"/ref=.*").unwrap();
+    }
+    let url = url.trim();
+    let caps = match RE_URL.captures(url) {
+        Some(c) => c,
+        None => return url.to_owned(),
+    };
+
+    let host = caps.get(1).map(|m| m.as_str()).unwrap_or("").to_lowercase();
+    let host = host.trim_end_matches(":443").trim_end_matches(":80");
+    let host = host.trim_start_matches("www.");
+
+    let path = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+    let path = RE_REF.replace(path, "");
+    let path = path.trim_end_matches('/');
+
+    let params: Vec<&str> = caps
+        .get(3)
+        .map(|m| m.as_str().trim_start_matches('?'))
+        .unwrap_or("")
+        .split('&')
+        .filter(|p| p.len() > 0 && !is_tracking_param(p))
+        .collect();
+
+    let mut c_url = format!("https://{}{}", host, path);
+    if params.len() > 0 {
+        c_url.push('?');
+        c_url.push_str(&params.join("&"));
+    }
+    c_url
 }
 
-pub fn deserde_links() -> Vec<String> {
-    let read_links = String::from_utf8(
-        std::fs::read(LINKS_JSON_DIR)
-            .unwrap_or("Not Found".to_owned().into_bytes()),
-    )
-    .unwrap_or_default();
-
-    let deser_links: SpLinks = serde_json::from_str(&read_links)
-        .unwrap_or_default();
-
-    deser_links.links
+fn is_tracking_param(param: &str) -> bool {
+    let key = param.split('=').next().unwrap_or("").to_lowercase();
+    key.starts_with("utm_")
+        || ["fbclid", "gclid", "mc_cid", "mc_eid", "ref_src"].contains(&key.as_str())
 }
 
-pub fn serde_add_links(mut add: Vec<String>) {
-    let read_links = String::from_utf8(
-        std::fs::read(LINKS_JSON_DIR)
-            .unwrap_or("Not Found".to_owned().into_bytes()),
-    )
-    .unwrap_or_default();
-
-    let old_sp_links: SpLinks = serde_json::from_str(&read_links)
-        .unwrap_or_default();
-
-    let mut new_links = old_sp_links.links;
-    new_links.append(&mut add);
-
-    serde_links(new_links)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_url_same_page() {
+        let c = "https://a.com/blog/post";
+        assert_eq!(canonical_url("http://a.com/blog/post"), c);
+        assert_eq!(canonical_url("https://www.a.com/blog/post/"), c);
+        assert_eq!(canonical_url("HTTPS://A.com:443/blog/post#top"), c);
+        assert_eq!(canonical_url("https://a.com/blog/post?utm_source=x&utm_medium=y"), c);
+        assert_eq!(canonical_url("https://a.com/blog/post/ref=rss"), c);
+        assert_eq!(
+            canonical_url("https://a.com/blog/post/?id=1&fbclid=z#c"),
+            "https://a.com/blog/post?id=1"
+        );
+        assert_eq!(canonical_url("https://a.com/"), "https://a.com");
+        assert_eq!(canonical_url(""), "");
+    }
 }
@@ -4,3 +4,5 @@
 
 pub mod email;
 pub mod helper;
+pub mod simhash;
+pub mod thumb;
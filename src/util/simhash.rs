@@ -0,0 +1,103 @@
+// simhash fingerprint, to find the near-duplicate items
+
+// max hamming distance of 2 fingerprints to be taken as duplicate,
+// a bit loose as the text of an item is short
+pub const DUP_DISTANCE: u32 = 6;
+
+// too few words to tell, no fingerprint
+pub const MIN_TOKENS: usize = 8;
+
+// fingerprint per normalized title and content, 0 if too short;
+// as i64 to save in BIGINT
+pub fn item_fingerprint(title: &str, content: &str) -> i64 {
+    let title_tokens = tokenize(title);
+    let content_tokens = tokenize(content);
+    if title_tokens.len() + content_tokens.len() < MIN_TOKENS {
+        return 0;
+    }
+
+    // words as features, the title counts double
+    let mut features: Vec<(String, i32)> = Vec::new();
+    for w in title_tokens {
+        features.push((w, 2));
+    }
+    for w in content_tokens {
+        features.push((w, 1));
+    }
+
+    simhash(&features) as i64
+}
+
+pub fn simhash(features: &[(String, i32)]) -> u64 {
+    let mut v = [0i32; 64];
+    for (f, w) in features {
+        let h = fnv1a(f.as_bytes());
+        for (i, bit) in v.iter_mut().enumerate() {
+            if (h >> i) & 1 == 1 {
+                *bit += *w;
+            } else {
+                *bit -= *w;
+            }
+        }
+    }
+
+    let mut hash: u64 = 0;
+    for (i, bit) in v.iter().enumerate() {
+        if *bit > 0 {
+            hash |= 1 << i;
+        }
+    }
+    hash
+}
+
+pub fn hamming(a: i64, b: i64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+pub fn is_near_dup(a: i64, b: i64) -> bool {
+    a != 0 && b != 0 && hamming(a, b) <= DUP_DISTANCE
+}
+
+// lowercase words, w/o punctuation
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() > 0)
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+// stable across builds, unlike the std hasher, as saved in db
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_dup_fingerprint() {
+        let title = "Understanding Async Rust: Futures, Executors and Wakers";
+        let content = "In this post we take a close look at how futures are \
+            polled by an executor, what a waker does, and why pinning matters \
+            when you write your own async runtime in Rust.";
+        let a = item_fingerprint(title, content);
+        let b = item_fingerprint(
+            &title.to_uppercase(),
+            &(content.to_owned() + " Cross-posted."),
+        );
+        let c = item_fingerprint(
+            "A Tour of Database Indexes in Postgres",
+            "B-tree, hash, GIN and GiST indexes, when to use each of them, \
+            and how the planner chooses among them for your queries.",
+        );
+        assert!(is_near_dup(a, b));
+        assert!(!is_near_dup(a, c));
+        assert_eq!(item_fingerprint("Hello", "world"), 0);
+    }
+}
@@ -0,0 +1,118 @@
+// cache the logo of items as a local thumbnail, not hotlink the remote image
+
+use image::{DynamicImage, GenericImageView, ImageOutputFormat};
+use log::error;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::bot::crawler::CRAWLER;
+use crate::errors::{ServiceError, ServiceResult};
+
+// the url served
+pub const THUMB_URL: &str = "/static/thumb";
+
+lazy_static! {
+    // where to save: env THUMB_DIR, else static/thumb in the cwd, as the static files
+    pub static ref THUMB_DIR: PathBuf = dotenv::var("THUMB_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("static/thumb"));
+}
+
+// fit in a box of the size, not upscale
+pub const THUMB_SIZE: u32 = 320;
+
+// not download a larger image
+pub const MAX_IMAGE_BYTES: u64 = 8 * 1024 * 1024;
+
+pub fn is_local(logo: &str) -> bool {
+    logo.starts_with(THUMB_URL)
+}
+
+// the url of local thumbnail, or keep the origin if failed
+pub fn cache_thumb(logo: &str) -> String {
+    let logo = logo.trim();
+    if logo.len() == 0 || is_local(logo) || !logo.starts_with("http") {
+        return logo.to_owned();
+    }
+    match save_thumb(logo) {
+        Ok(url) => url,
+        Err(e) => {
+            error!("thumb {}: {}", logo, e);
+            logo.to_owned()
+        }
+    }
+}
+
+fn save_thumb(logo: &str) -> ServiceResult<String> {
+    let bytes = CRAWLER.fetch_bytes(logo, MAX_IMAGE_BYTES)?;
+    let img = image::load_from_memory(&bytes)
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+    let (data, ext) = make_thumb(&img)?;
+
+    // the same image saved once
+    let name = format!("{}.{}", content_hash(&data), ext);
+    let path = THUMB_DIR.join(&name);
+    if !path.exists() {
+        fs::create_dir_all(&*THUMB_DIR)?;
+        fs::write(&path, &data)?;
+    }
+
+    Ok(format!("{}/{}", THUMB_URL, name))
+}
+
+// resize and encode: png if transparent, else jpeg
+pub fn make_thumb(img: &DynamicImage) -> ServiceResult<(Vec<u8>, &'static str)> {
+    let (w, h) = img.dimensions();
+    let thumb = if w > THUMB_SIZE || h > THUMB_SIZE {
+        img.thumbnail(THUMB_SIZE, THUMB_SIZE)
+    } else {
+        img.clone()
+    };
+
+    let mut data: Vec<u8> = Vec::new();
+    let ext = if thumb.color().has_alpha() {
+        thumb.write_to(&mut data, ImageOutputFormat::Png)
+            .map_err(|e| ServiceError::InternalServerError(e.to_string()))?;
+        "png"
+    } else {
+        thumb.write_to(&mut data, ImageOutputFormat::Jpeg(85))
+            .map_err(|e| ServiceError::InternalServerError(e.to_string()))?;
+        "jpg"
+    };
+
+    Ok((data, ext))
+}
+
+// hex of sha256, cut to keep the name short
+fn content_hash(data: &[u8]) -> String {
+    let hex = format!("{:x}", Sha256::digest(data));
+    hex.chars().take(32).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb, Rgba};
+
+    #[test]
+    fn thumb_of_image() {
+        let photo = DynamicImage::ImageRgb8(
+            ImageBuffer::from_pixel(1200, 600, Rgb([9, 99, 199]))
+        );
+        let (data, ext) = make_thumb(&photo).unwrap();
+        assert_eq!(ext, "jpg");
+        let t = image::load_from_memory(&data).unwrap();
+        assert_eq!(t.dimensions(), (THUMB_SIZE, THUMB_SIZE / 2));
+
+        let icon = DynamicImage::ImageRgba8(
+            ImageBuffer::from_pixel(64, 64, Rgba([0, 0, 0, 0]))
+        );
+        let (data, ext) = make_thumb(&icon).unwrap();
+        assert_eq!(ext, "png");
+        assert_eq!(image::load_from_memory(&data).unwrap().dimensions(), (64, 64));
+
+        assert_eq!(content_hash(b"toplog").len(), 32);
+        assert!(is_local("/static/thumb/a.jpg"));
+    }
+}
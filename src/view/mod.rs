@@ -10,6 +10,8 @@ use chrono::NaiveDate;
 pub use askama::Template;
 
 use crate::api::item::{Item};
+use crate::api::revision::{ItemRevision};
+use crate::api::bookmark::{SavedItem};
 use crate::api::blog::{Blog};
 use crate::api::auth::CheckUser;
 
@@ -39,6 +41,9 @@ pub struct CollectionTmpl<'a> {
     pub items: &'a Vec<Item>,
     pub blogs: &'a Vec<Blog>,
     pub tys: &'a Vec<&'a str>,
+    pub next: &'a str,  // cursor of the next page
+    pub my_votes: &'a HashMap<i32, i16>,  // of the caller, empty if cached
+    pub csrf_tok: &'a str,  // for bookmarks, empty if cached
 }
 
 #[derive(Template)]
@@ -47,11 +52,36 @@ pub struct ItemTmpl<'a> {
     pub item: &'a Item,
 }
 
+#[derive(Template)]
+#[template(path = "item_history.html")]
+pub struct ItemHistoryTmpl<'a> {
+    pub item: &'a Item,
+    pub revisions: &'a Vec<ItemRevision>,
+}
+
+#[derive(Template)]
+#[template(path = "search.html")]
+pub struct SearchTmpl<'a> {
+    pub q: &'a str,
+    pub topic: &'a str,
+    pub ty: &'a str,
+    pub page: i32,
+    pub has_more: bool,
+    pub items: &'a Vec<Item>,
+    pub blogs: &'a Vec<Blog>,
+    pub topics: &'a Vec<&'a str>,
+    pub tys: &'a Vec<&'a str>,
+    pub my_votes: &'a HashMap<i32, i16>,
+    pub csrf_tok: &'a str,
+}
+
 #[derive(Template)]
 #[template(path = "more_item.html")]
 pub struct ItemsTmpl<'a> {
     pub items: &'a Vec<Item>,
     pub topic: &'a str,
+    pub next: &'a str,  // cursor of the next page
+    pub my_votes: &'a HashMap<i32, i16>,
 }
 
 #[derive(Template)]
@@ -61,6 +91,24 @@ pub struct ProfileTmpl<'a> {
     pub is_self: bool,
 }
 
+#[derive(Template)]
+#[template(path = "moderation.html")]
+pub struct ModerationTmpl<'a> {
+    pub items: &'a Vec<Item>,
+    pub topics: &'a Vec<&'a str>,
+    pub tys: &'a Vec<&'a str>,
+}
+
+#[derive(Template)]
+#[template(path = "readlist.html")]
+pub struct ReadListTmpl<'a> {
+    pub saved: &'a Vec<SavedItem>,
+    pub read: &'a str,  // all|read|unread
+    pub page: i32,
+    pub has_more: bool,
+    pub csrf_tok: &'a str,
+}
+
 #[derive(Template)]
 #[template(path = "about.html")]
 pub struct AboutTmpl();
@@ -87,6 +135,7 @@ mod filters {
     use chrono_tz::Tz;
     use unic_segment::GraphemeIndices;
     use log::error;
+    use std::collections::HashMap;
 
     pub fn host(s: &str) -> TmplResult<String> {
         use crate::util::helper::get_host;
@@ -94,6 +143,11 @@ mod filters {
         Ok(s_host)
     }
 
+    // the vote of the caller on the item: 1 vote, -1 veto, 0 none
+    pub fn my_vote(id: &i32, my_votes: &HashMap<i32, i16>) -> TmplResult<i16> {
+        Ok(my_votes.get(id).cloned().unwrap_or(0))
+    }
+
     pub fn num_unit(num: &i32) -> TmplResult<String> {
         let x = *num;
         let (n, u): (i32, &str) = if x > 9000 {
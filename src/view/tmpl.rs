@@ -2,9 +2,11 @@
 //use futures::{Future};
 use actix::{Handler, Message};
 use crate::errors::{ServiceError, ServiceResult};
-use crate::api::auth::{verify_token, QueryUser, CheckUser, CheckCan};
-use crate::api::item::{Item, QueryItems};
+use crate::api::auth::{verify_token, generate_token, QueryUser, CheckUser, CheckCan};
+use crate::api::item::{Item, QueryItems, QueryPending, PageArgs, ItemFilter};
 use crate::api::blog::{Blog, QueryBlogs};
+use crate::api::search::{SearchQuery, SearchResult, SEARCH_PERPAGE};
+use crate::api::bookmark::{QueryBookmarks, ReadQuery};
 use crate::{Dba, DbAddr, PooledConn};
 use actix_web::{
     web::{Data, Path, Query},
@@ -13,10 +15,11 @@ use actix_web::{
 };
 use chrono::{SecondsFormat, Utc};
 use log::error;
+use std::collections::HashMap;
 use crate::view::{
     Template, TY_VEC, TOPIC_VEC, 
     CollectionTmpl, ItemTmpl, ItemsTmpl, AboutTmpl, ProfileTmpl,
-    SiteMapTmpl
+    SiteMapTmpl, ModerationTmpl, ItemHistoryTmpl, SearchTmpl, ReadListTmpl
 };
 use crate::api::auth::{CheckTopic};
 
@@ -24,10 +27,18 @@ use crate::api::auth::{CheckTopic};
 // 
 #[derive(Deserialize, Clone)]
 pub struct PageQuery {
-    page: i32,
+    page: Option<i32>,
     perpage: i32,
+    after: Option<String>,  // cursor, over page
 }
 
+#[derive(Deserialize, Clone)]
+pub struct ListPageQuery {
+    page: Option<i32>,
+}
+
+pub const READLIST_PERPAGE: i32 = 42;
+
 #[derive(Deserialize, Clone)]
 pub struct PerQuery {
     ty: Option<String>,    // Article|Book...
@@ -48,22 +59,24 @@ pub struct FromQuery {
 pub async fn dyn_index(
     db: Data<DbAddr>,
     c_tpc: CheckTopic,
+    auth: Option<CheckUser>,
 ) -> ServiceResult<HttpResponse> {
     let q = Query(PerQuery{
         ty: None,
         tpc: None,
         ord: None
     });
-    collection_dyn(db, q, c_tpc).await
+    collection_dyn(db, q, c_tpc, auth).await
 }
 
 // GET /collection?ty=&tpc=&ord=
 //
-// static file default, otherwise generate
+// static file default, otherwise generate; per user if signed in, for the votes
 pub async fn collection_either(
     db: Data<DbAddr>,
     q: Query<PerQuery>,
     c_tpc: CheckTopic,
+    auth: Option<CheckUser>,
 ) -> ServiceResult<HttpResponse> {
     let pq = q.clone();
     let ty = pq.ty.unwrap_or(String::from("index"));
@@ -75,6 +88,10 @@ pub async fn collection_either(
     if tpc.trim() == "from" {
         tpc = String::from("all");
     }
+    // hot changes w/ time, not cached
+    if ty == "hot" || auth.is_some() {
+        return collection_dyn(db, q, c_tpc, auth).await
+    }
     let dir = "www/collection/".to_owned() + &tpc +"-" + &ty + ".html";
     let s_html = std::fs::read(dir);
     match s_html {
@@ -86,7 +103,7 @@ pub async fn collection_either(
             )
         }
         _ => {
-            return collection_dyn(db, q, c_tpc).await
+            return collection_dyn(db, q, c_tpc, auth).await
         }
     }
 }
@@ -95,6 +112,7 @@ pub async fn collection_dyn(
     db: Data<DbAddr>,
     q: Query<PerQuery>,
     c_tpc: CheckTopic,
+    auth: Option<CheckUser>,
 ) -> ServiceResult<HttpResponse> {
     let pq = q.clone();
     let ty = pq.ty.unwrap_or(String::from("Misc"));
@@ -110,7 +128,11 @@ pub async fn collection_dyn(
         topic: topic.clone(), 
         ty: ty.clone(),
         page: 1,
+        after: None,
+        uname: auth.map(|a| a.uname),
     };
+    let is_anon = tpc_msg.uname.is_none();
+    let csrf_tok = bookmark_csrf(tpc_msg.uname.as_deref());
     
     let res = db.send(tpc_msg).await?;
     match res {
@@ -125,11 +147,17 @@ pub async fn collection_dyn(
                 items: &msg.items,
                 blogs: &msg.blogs,
                 tys: &TY_VEC,
+                next: msg.next.as_deref().unwrap_or_default(),
+                my_votes: &msg.my_votes,
+                csrf_tok: &csrf_tok,
             };
 
             let h = tmpl.render().unwrap_or("Rendering failed".into());
-            let dir = "www/collection/".to_owned() + &msg.message + ".html";
-            std::fs::write(dir, h.as_bytes())?;
+            // cache the one w/o votes only
+            if ty != "hot" && is_anon {
+                let dir = "www/collection/".to_owned() + &msg.message + ".html";
+                std::fs::write(dir, h.as_bytes())?;
+            }
             Ok(HttpResponse::Ok().content_type("text/html").body(h))
         }
         Err(e) => { error!("{}", e);  Ok(e.error_response()) },
@@ -142,6 +170,7 @@ pub async fn collection_dyn(
 pub async fn item_from(
     db: Data<DbAddr>,
     bq: Query<FromQuery>,
+    auth: Option<CheckUser>,
 ) -> ServiceResult<HttpResponse> {
     // extract Query
     let bq_by = bq.into_inner().by.unwrap_or_default();
@@ -152,7 +181,10 @@ pub async fn item_from(
         topic: String::from("from"),
         ty: by,
         page: 1, 
+        after: None,
+        uname: auth.map(|a| a.uname),
     };
+    let csrf_tok = bookmark_csrf(topic_msg.uname.as_deref());
     
     if let Err(e) = topic_msg.validate() {
         error!("{}", e);
@@ -171,6 +203,9 @@ pub async fn item_from(
                 items: &msg.items,
                 blogs: &msg.blogs,
                 tys: &TY_VEC,
+                next: msg.next.as_deref().unwrap_or_default(),
+                my_votes: &msg.my_votes,
+                csrf_tok: &csrf_tok,
             };
 
             let h = by_tmpl.render().unwrap_or("Rendering failed".into());
@@ -182,13 +217,14 @@ pub async fn item_from(
     }
 }
 
-// GET /moreitems/{topic}/{ty}?page=&perpage=42 
+// GET /moreitems/{topic}/{ty}?after=&perpage=42 
 // pagination
 //
 pub async fn more_item(
     db: Data<DbAddr>,
     p: Path<(String, String)>,
     pq: Query<PageQuery>,
+    auth: Option<CheckUser>,
 ) -> ServiceResult<HttpResponse> {
     let pa = p.into_inner();
     let topic = pa.0;
@@ -199,11 +235,12 @@ pub async fn more_item(
     } else { 
         p_ty 
     };
-    // extract Query
-    let page = std::cmp::max(pq.page, 1);
-    let perpage = pq.clone().perpage;
+    // extract Query, the cursor goes first
+    let page = std::cmp::max(pq.page.unwrap_or(1), 1);
+    let after = pq.clone().after;
 
-    let topic_msg = Topic{ topic, ty, page };
+    let uname = auth.map(|a| a.uname);
+    let topic_msg = Topic{ topic, ty, page, after, uname };
 
     if let Err(e) = topic_msg.validate() {
         error!("{}", e);
@@ -223,6 +260,8 @@ pub async fn more_item(
             let items_tmpl = ItemsTmpl {
                 items: &msg.items,
                 topic: tpc,
+                next: msg.next.as_deref().unwrap_or_default(),
+                my_votes: &msg.my_votes,
             };
 
             let h = items_tmpl.render().unwrap_or("Rendering failed".into());
@@ -286,6 +325,84 @@ pub async fn item_view_dyn(
     }
 }
 
+// GET /item/{id}/history
+//
+// the revisions of item, newest first
+pub async fn item_history(
+    db: Data<DbAddr>,
+    p: Path<i32>,
+) -> ServiceResult<HttpResponse> {
+    let id = p.into_inner();
+    use crate::api::item::QueryItem;
+    use crate::api::revision::QueryRevisions;
+
+    let res = db.send(QueryItem { 
+        id, 
+        method: String::from("GET"), 
+        uname: String::new(),
+    }).await?;
+    let item = match res {
+        Ok(i) => i,
+        Err(e) => { error!("{}", e); return Ok(e.error_response()) }
+    };
+    let revisions = match db.send(QueryRevisions { item_id: id }).await? {
+        Ok(r) => r,
+        Err(e) => { error!("{}", e); return Ok(e.error_response()) }
+    };
+
+    let history_tmpl = ItemHistoryTmpl {
+        item: &item,
+        revisions: &revisions,
+    };
+    let s = history_tmpl.render().unwrap_or("Rendering failed".into());
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(s)
+    )
+}
+
+// GET /search?q=&topic=&ty=&page=
+//
+pub async fn search(
+    db: Data<DbAddr>,
+    sq: Query<SearchQuery>,
+    auth: Option<CheckUser>,
+) -> ServiceResult<HttpResponse> {
+    let s = SearchQuery { uname: auth.map(|a| a.uname), ..sq.into_inner() };
+    let csrf_tok = bookmark_csrf(s.uname.as_deref());
+
+    // an empty query renders the search box only
+    let res = if s.validate().is_ok() {
+        match db.send(s.clone()).await? {
+            Ok(r) => r,
+            Err(e) => { error!("{}", e); return Ok(e.error_response()) }
+        }
+    } else {
+        SearchResult::default()
+    };
+
+    let search_tmpl = SearchTmpl {
+        q: s.q.trim(),
+        topic: s.topic().unwrap_or("all"),
+        ty: s.ty().unwrap_or(""),
+        page: s.page(),
+        has_more: res.items.len() as i32 == SEARCH_PERPAGE,
+        items: &res.items,
+        blogs: &res.blogs,
+        topics: &TOPIC_VEC,
+        tys: &TY_VEC,
+        my_votes: &res.my_votes,
+        csrf_tok: &csrf_tok,
+    };
+    let h = search_tmpl.render().unwrap_or("Rendering failed".into());
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(h)
+    )
+}
+
 // profile
 // GET /@{uname}
 //
@@ -311,6 +428,72 @@ pub async fn profile(
     )
 }
 
+// GET /moderation
+//
+// review the bot items: approve, edit or reject
+pub async fn moderation(
+    db: Data<DbAddr>,
+    _auth: CheckCan,
+) -> ServiceResult<HttpResponse> {
+    let items = match db.send(QueryPending).await? {
+        Ok(i) => i,
+        Err(e) => { error!("{}", e); return Ok(e.error_response()) }
+    };
+
+    let mod_tmpl = ModerationTmpl {
+        items: &items,
+        topics: &TOPIC_VEC,
+        tys: &TY_VEC,
+    };
+    let s = mod_tmpl.render().unwrap_or("Rendering failed".into());
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(s)
+    )
+}
+
+// GET /readlist?read=true|false&page=
+//
+// the bookmarks of the user, private
+pub async fn readlist(
+    db: Data<DbAddr>,
+    auth: CheckUser,
+    rq: Query<ReadQuery>,
+    pq: Query<ListPageQuery>,
+) -> ServiceResult<HttpResponse> {
+    let page = std::cmp::max(pq.page.unwrap_or(1), 1);
+    let csrf_tok = bookmark_csrf(Some(&auth.uname));
+    let q = QueryBookmarks {
+        uname: auth.uname,
+        read: rq.read,
+        perpage: READLIST_PERPAGE,
+        page,
+    };
+    let msg = match db.send(q).await? {
+        Ok(m) => m,
+        Err(e) => { error!("{}", e); return Ok(e.error_response()) }
+    };
+
+    let read_tmpl = ReadListTmpl {
+        saved: &msg.saved,
+        read: match rq.read {
+            Some(true) => "read",
+            Some(false) => "unread",
+            None => "all",
+        },
+        page,
+        has_more: ((page * READLIST_PERPAGE) as i64) < msg.count,
+        csrf_tok: &csrf_tok,
+    };
+    let s = read_tmpl.render().unwrap_or("Rendering failed".into());
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(s)
+    )
+}
+
 // GET /site/{name}
 //
 // site: about, help, terms, etc.
@@ -383,6 +566,14 @@ pub fn del_dir(dir_name: &str) -> ServiceResult<()> {
 
 // for general erro response
 //
+// for the bookmark requests, none if anonymous
+fn bookmark_csrf(uname: Option<&str>) -> String {
+    match uname {
+        Some(u) => generate_token(u, "bookmark@csrf", 1*24*3600).unwrap_or_default(),
+        None => String::new(),
+    }
+}
+
 pub async fn blank_response() -> ServiceResult<HttpResponse> {
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
@@ -401,6 +592,8 @@ pub struct ItemBlogMsg {
     pub message: String,  // send back topic-ty
     pub items: Vec<Item>,
     pub blogs: Vec<Blog>,
+    pub next: Option<String>,  // cursor of the next page
+    pub my_votes: HashMap<i32, i16>,  // of the caller, by item id
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -408,6 +601,8 @@ pub struct Topic{
     pub topic: String,  // special case: all, from
     pub ty: String,     // special case: index, Misc, newest
     pub page: i32,
+    pub after: Option<String>,  // cursor, over page
+    pub uname: Option<String>,  // the caller, for my_votes
 }
 
 impl Topic {
@@ -449,35 +644,27 @@ impl Handler<Topic> for Dba {
 
         let tp = tpc.trim().to_lowercase();
 
-        let (query_item, query_blog) = match tp.trim() {
-            "all" => {
-                (
-                    QueryItems::Index(typ, 42, t.page),
-                    QueryBlogs::Index("index".into(), 42, 1)
-                )
-            }
-            "from" => {
-                (
-                    QueryItems::Author(typ.clone(), 42, t.page),
-                    QueryBlogs::Name(typ, 42, 1)
-                )
-            } 
-            _ => {
-                (
-                    QueryItems::Tt(tpc.clone(), typ, 42, t.page),
-                    QueryBlogs::Top(tpc, 42, 1)
-                )
-            }
+        let query_item = QueryItems {
+            filter: ItemFilter::of_collection(&tpc, &typ),
+            page: PageArgs::new(42, t.page).cursor(t.after.as_deref(), None),
+            uname: t.uname,
+        };
+        let query_blog = match tp.trim() {
+            "all" => QueryBlogs::Index("index".into(), 42, 1),
+            "from" => QueryBlogs::Name(typ, 42, 1),
+            _ => QueryBlogs::Top(tpc, 42, 1),
         };
 
-        let (i_list, _) = query_item.get(conn)?;
+        let i_msg = query_item.get(conn)?;
         let (b_list, _) = query_blog.get(conn)?;
 
         Ok(ItemBlogMsg {
             status: 201,
             message: msg, // send back the topic-ty info
-            items: i_list,
+            items: i_msg.items,
             blogs: b_list,
+            next: i_msg.next,
+            my_votes: i_msg.my_votes,
         })
     }
 }
@@ -494,7 +681,8 @@ fn checker(ty: &str) -> bool {
         || ty == "Project" 
         || ty == "Translate"
         || ty == "Misc"
-        || ty == "newest";
+        || ty == "newest"
+        || ty == "hot";
 
     check
 }
@@ -0,0 +1,20 @@
+// revert item to before a revision, admin only
+
+document.addEventListener('DOMContentLoaded', function() {
+  if (getCookie("oMg") !== 'true') return;
+  let links = document.getElementsByClassName('hide-link');
+  for (let l of links) { l.classList.remove('hide-link'); }
+})
+
+function revertItem(id, rid) {
+  if (getCookie("oMg") !== 'true') return;
+  if (!confirm(`Undo revision #${rid} and the later ones?`)) return;
+  let tok = getCookie(TOK);
+  fetch(`/api/items/${id}/revisions/${rid}`, {
+    method: 'PUT',
+    headers: { 'Authorization': tok },
+  }).then(res => {
+    if (!res.ok) { alert('Failed'); return; }
+    window.location.reload();
+  });
+}
@@ -88,20 +88,26 @@ window.onclick = function(event) {
 
 function onSearch(selector) {
   let q = document.getElementById(selector);
-  if (q && q.value != "") {
-    let openUrl = 'https://www.google.com/search?q=site:toplog.cc/%20';
-    window.open(openUrl + q.value, "_blank");
+  if (q && q.value.trim() != "") {
+    window.location.href = '/search?q=' + encodeURIComponent(q.value.trim());
   }
 }
 
 const PerPage = 42; 
-let idxPage = 1;
 let hasMoreIdx = true;
 function loadMoreItems(topic='all', ty='Article') {
   if (!hasMoreIdx) { return; }
-  idxPage += 1;
+  // the cursor of next page, in the last loaded
+  let list = document.getElementById('item-list');
+  let marks = list.getElementsByClassName('next-cursor');
+  let after = marks.length > 0 ? marks[marks.length - 1].dataset.next : '';
+  if (!after) {
+    console.log("No More");
+    hasMoreIdx = false;
+    return;
+  }
   fetch(
-    `/moreitems/${topic}/${ty}?page=${idxPage}&perpage=${PerPage}`
+    `/moreitems/${topic}/${ty}?after=${after}&perpage=${PerPage}`
   ).then(resp => {
     //console.log(resp);
     resp.text().then( r => {
@@ -111,7 +117,7 @@ function loadMoreItems(topic='all', ty='Article') {
         hasMoreIdx = false;
       }
       window.scrollTo(0, document.body.scrollHeight);
-      document.getElementById('item-list').innerHTML += r;
+      list.innerHTML += r;
     })
   });
 }
@@ -130,30 +136,87 @@ function toggleTop(id) {
   });
 }
 
+// count the click for hot ranking, not wait; once per user, w/ the cookie
+function clickItem(id) {
+  if (!getCookie(TOK)) return;
+  let url = `/api/items/${id}/click`;
+  if (navigator.sendBeacon) {
+    navigator.sendBeacon(url);
+  } else {
+    fetch(url, { method: 'POST', keepalive: true });
+  }
+}
+
+// vote, or clear if voted
 function upVote(id) {
+  let upEle = document.getElementById("up-" + id);
+  let voted = upEle && upEle.dataset.my === '1';
+  voteItem(id, voted ? 'clear' : 'vote');
+}
+
+// action: vote|veto|clear, the other switches
+function voteItem(id, action) {
   let tok = getCookie(TOK);
   let check = Boolean(tok);
   if (!check) {
     window.location.href = "/auth?to=signin";
     return;
   }
-  fetch(`/api/items/${id}?action=vote`, {
+  fetch(`/api/items/${id}?action=${action}`, {
     method: 'PUT', 
     headers: { 'Authorization': tok },
   }).then(res => {
+    if (!res.ok) return;
     res.json().then(r => {
       //console.log(r);
       let voteEle = document.getElementById("vote-" + id);
-      if (voteEle) { 
-        //let voteNum = Number(voteEle.innerText);
-        voteEle.innerText = r; 
-        let upEle = document.getElementById("up-" + id);
-        if (upEle) { upEle.hidden = true }
+      if (voteEle) { voteEle.innerText = r.vote; }
+      let upEle = document.getElementById("up-" + id);
+      if (upEle) { 
+        upEle.dataset.my = r.my_vote;
+        upEle.classList.toggle('voted', r.my_vote === 1);
+        upEle.title = r.my_vote === 1 ? 'Voted, Click to Clear' : 'Read Again Later';
+      }
+      let vetoEle = document.getElementById("veto-" + id);
+      if (vetoEle) {
+        vetoEle.innerText = r.my_vote === -1 ? 'UnVeto' : 'Veto';
+        vetoEle.onclick = () => voteItem(id, r.my_vote === -1 ? 'clear' : 'veto');
       }
     }) 
   });
 }
 
+// the csrf token of bookmark requests, rendered in page
+function getBookmarkCsrf() {
+  let csrfEle = document.getElementById('bookmark-csrf');
+  return csrfEle ? csrfEle.value : '';
+}
+
+// save to read later, private, apart from votes
+function saveItem(id) {
+  let tok = getCookie(TOK);
+  if (!tok) {
+    window.location.href = "/auth?to=signin";
+    return;
+  }
+  fetch('/api/bookmarks', {
+    method: 'POST', 
+    headers: { 
+      'Authorization': tok, 
+      'CsrfToken': getBookmarkCsrf(),
+      'Content-Type': 'application/json' 
+    },
+    body: JSON.stringify({ item_id: Number(id) }),
+  }).then(res => {
+    if (!res.ok) return;
+    let saveEle = document.getElementById("save-" + id);
+    if (saveEle) { 
+      saveEle.innerText = 'Saved';
+      saveEle.onclick = () => { window.location.href = '/readlist'; };
+    }
+  });
+}
+
 // md parse in backend
 function showFull(id) {
   let mdSelector = 'md-' + id;
@@ -0,0 +1,49 @@
+// review the bot items: approve, edit or reject in bulk
+
+function checkAll(checked) {
+  let boxes = document.getElementsByClassName('pend-check');
+  for (let box of boxes) { box.checked = checked; }
+}
+
+function checkedIds() {
+  let ids = [];
+  let boxes = document.getElementsByClassName('pend-check');
+  for (let box of boxes) {
+    if (box.checked) { ids.push(Number(box.value)); }
+  }
+  return ids;
+}
+
+function moderate(action) {
+  let ids = checkedIds();
+  if (ids.length == 0) return;
+  let topic = document.getElementById('mod-topic').value || null;
+  let ty = document.getElementById('mod-ty').value || null;
+  if (action == 'edit' && !topic && !ty) return;
+  if (action == 'reject' && !confirm(`Reject ${ids.length} items?`)) return;
+
+  let tok = getCookie(TOK);
+  fetch('/api/pending', {
+    method: 'PUT',
+    headers: {
+      'Authorization': tok,
+      'Content-Type': 'application/json'
+    },
+    body: JSON.stringify({ action, ids, topic, ty })
+  }).then(res => {
+    if (!res.ok) { alert('Failed'); return; }
+    for (let id of ids) {
+      if (action == 'edit') {
+        if (topic) { document.getElementById('pend-tpc-' + id).innerText = topic; }
+        if (ty) { document.getElementById('pend-ty-' + id).innerText = ty; }
+        document.getElementById('pend-' + id).checked = false;
+      } else {
+        let row = document.getElementById('pend-row-' + id);
+        if (row) { row.remove(); }
+      }
+    }
+    let left = document.getElementsByClassName('pend-check').length;
+    document.getElementById('mod-count').innerText = `${left} pending`;
+    document.getElementById('pend-all').checked = false;
+  });
+}
@@ -3,6 +3,8 @@ let extKw;
 let page = 1;
 let totalCount;
 let hasMore = false;
+let nextCursor = '';
+let savedPage = 1;
 
 document.addEventListener('DOMContentLoaded', async function() {
   let srcSpan = document.getElementById('avatar-src');
@@ -13,7 +15,7 @@ document.addEventListener('DOMContentLoaded', async function() {
   await navTo('vote', 'rl');
 })
 
-// submit/vote
+// submit/vote/saved
 async function navTo(kw, id) {
   extKw = kw;
   await loadAndAppend(kw);
@@ -26,19 +28,33 @@ async function navTo(kw, id) {
 
 async function loadMoreAny() {
   if (!hasMore) return;
-  page += 1;
   await loadAndAppend(extKw, true);
 }
 
 // load list and generate html then append to page
 async function loadAndAppend(action, isMore=false) {
-  let url = `/api/getitems/user?per=${uname}&kw=${action}&page=${page}&perpage=${PerPage}`;
-  let resp = await fetch(url);
+  let isSaved = action === 'saved';
+  let resp;
+  if (isSaved) {
+    // the bookmarks, private to self
+    savedPage = isMore ? savedPage + 1 : 1;
+    resp = await fetch(
+      `/api/bookmarks?page=${savedPage}&perpage=${PerPage}`,
+      { headers: { 'Authorization': getCookie(TOK) } }
+    );
+  } else {
+    let after = isMore ? `&after=${nextCursor}` : '';
+    let url = `/api/getitems/user?per=${uname}&kw=${action}&page=${page}&perpage=${PerPage}${after}`;
+    resp = await fetch(url);
+  }
   if (!resp.ok) return;
   let res = await resp.json();
-  let objs = res.items;
-  if (page == 1) { totalCount = res.count; }
-  hasMore = page <= Math.floor(totalCount / PerPage);
+  let objs = isSaved
+    ? res.saved.map(s => Object.assign({}, s.item, { note: s.bookmark.note }))
+    : res.items;
+  totalCount = res.count;
+  nextCursor = res.next || '';
+  hasMore = isSaved ? savedPage * PerPage < totalCount : Boolean(nextCursor);
 
   let moreBtn = document.getElementById('loadmore-btn');
   if (moreBtn) { 
@@ -57,7 +73,7 @@ async function loadAndAppend(action, isMore=false) {
     let sum = document.createElement('section');
     sum.className = 's-sum-info';
     let title = obj.title;
-    let intro = obj.content;
+    let intro = obj.note || obj.content;
     let link = obj.link || ('/item/' + obj.id);
     let inner = `
       <a class="title-link" href="${link}" target="_blank" rel="noopener">
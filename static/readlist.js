@@ -0,0 +1,56 @@
+// the reading list: mark read or unread, note, remove
+
+function updateSaved(id, change) {
+  let tok = getCookie(TOK);
+  return fetch(`/api/bookmarks/${id}`, {
+    method: 'PUT',
+    headers: {
+      'Authorization': tok,
+      'CsrfToken': getBookmarkCsrf(),
+      'Content-Type': 'application/json'
+    },
+    body: JSON.stringify(change)
+  }).then(res => res.ok ? res.json() : null);
+}
+
+function markRead(id, isRead) {
+  updateSaved(id, { is_read: isRead }).then(b => {
+    if (!b) return;
+    let row = document.getElementById('saved-' + id);
+    if (row) { row.classList.toggle('saved-read', b.is_read); }
+    let readEle = document.getElementById('read-' + id);
+    if (readEle) {
+      readEle.dataset.read = b.is_read;
+      readEle.innerText = b.is_read ? 'Unread' : 'Read';
+    }
+  });
+}
+
+function toggleRead(id) {
+  let readEle = document.getElementById('read-' + id);
+  let isRead = readEle && readEle.dataset.read === 'true';
+  markRead(id, !isRead);
+}
+
+function editNote(id) {
+  let noteEle = document.getElementById('note-' + id);
+  let note = prompt('Note', noteEle ? noteEle.innerText : '');
+  if (note === null) return;
+  updateSaved(id, { note }).then(b => {
+    if (!b) { alert('Failed'); return; }
+    if (noteEle) { noteEle.innerText = b.note; }
+  });
+}
+
+function removeSaved(id) {
+  if (!confirm('Remove from the reading list?')) return;
+  let tok = getCookie(TOK);
+  fetch(`/api/bookmarks/${id}`, {
+    method: 'DELETE',
+    headers: { 'Authorization': tok, 'CsrfToken': getBookmarkCsrf() },
+  }).then(res => {
+    if (!res.ok) { alert('Failed'); return; }
+    let row = document.getElementById('saved-' + id);
+    if (row) { row.remove(); }
+  });
+}
@@ -0,0 +1,58 @@
+// the crawl rules, of source in db or per domain in bot/cfg.rs, against the saved
+// pages in tests/fixtures/spider, a site redesign fails here instead of items
+// quietly missing; to add or refresh one:
+// cargo run --bin spider -- snapshot <url> [--article <url>] [--prefix ..] [--regex ..] [--selector ..]
+
+#![cfg(feature = "fixture")]
+
+use std::path::Path;
+
+use srv::bot::cfg::get_source_links;
+use srv::bot::fixture::{Fixture, FIXTURE_DIR};
+use srv::bot::spider::parse_common_page;
+
+fn fixtures() -> Vec<Fixture> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURE_DIR);
+    Fixture::list(&root).expect("read fixtures dir")
+}
+
+#[test]
+fn links_of_sources() {
+    let mut failed: Vec<String> = Vec::new();
+    for fx in fixtures() {
+        let source = fx.source().unwrap_or_else(|e| panic!("{}: {}", fx.name(), e));
+        let (page, src, expected) = match source {
+            Some(s) => s,
+            None => continue,
+        };
+        // as the bot: the rules of source, or the per-domain rules
+        let got = get_source_links(&page, &src);
+        if got != expected {
+            failed.push(format!(
+                "{}:\n  expected: {:#?}\n  got: {:#?}",
+                fx.name(), expected, got
+            ));
+        }
+    }
+    assert!(failed.is_empty(), "links not as expected\n{}", failed.join("\n"));
+}
+
+#[test]
+fn info_of_articles() {
+    let mut failed: Vec<String> = Vec::new();
+    for fx in fixtures() {
+        let article = fx.article().unwrap_or_else(|e| panic!("{}: {}", fx.name(), e));
+        let (page, expected) = match article {
+            Some(a) => a,
+            None => continue,
+        };
+        let info = parse_common_page(page.get_html(), &page.url);
+        for (field, e, got) in expected.diff(&info) {
+            failed.push(format!(
+                "{} {}:\n  expected: {:?}\n  got: {:?}",
+                fx.name(), field, e, got
+            ));
+        }
+    }
+    assert!(failed.is_empty(), "page info not as expected\n{}", failed.join("\n"));
+}